@@ -0,0 +1,135 @@
+use crate::{json::Json, runner, Day};
+use std::time::Duration;
+
+/// Timings of one part over several runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timing {
+    pub day: u8,
+    pub part: u8,
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+/// Runs a part `warmup` times untimed, then `runs` times timed.
+/// Fails on the first error or panic.
+pub fn bench_part(
+    day: &Day,
+    part: u8,
+    input: &str,
+    warmup: usize,
+    runs: usize,
+) -> Result<Timing, String> {
+    let runs = runs.max(1);
+    let mut times = Vec::with_capacity(runs);
+    for i in 0..warmup + runs {
+        let run = runner::run_part(day, part, input);
+        run.answer?;
+        if i >= warmup {
+            times.push(run.elapsed);
+        }
+    }
+    times.sort();
+    let mid = times.len() / 2;
+    let median = if times.len() % 2 == 0 {
+        (times[mid - 1] + times[mid]) / 2
+    } else {
+        times[mid]
+    };
+    Ok(Timing {
+        day: day.number,
+        part,
+        runs,
+        min: times[0],
+        median,
+        max: times[times.len() - 1],
+    })
+}
+
+/// Median change against the baseline for the same day and part, as a fraction
+/// (0.1 is 10% slower). `None` if the baseline doesn't have it.
+pub fn change(timing: &Timing, baseline: &[Timing]) -> Option<f64> {
+    let base = baseline
+        .iter()
+        .find(|b| b.day == timing.day && b.part == timing.part)?;
+    let base = base.median.as_secs_f64();
+    (base > 0.0).then(|| timing.median.as_secs_f64() / base - 1.0)
+}
+
+pub fn to_json(timings: &[Timing]) -> Json {
+    let timings = timings.iter().map(|t| {
+        Json::object([
+            ("day", t.day.into()),
+            ("part", t.part.into()),
+            ("runs", t.runs.into()),
+            ("min_ns", (t.min.as_nanos() as u64).into()),
+            ("median_ns", (t.median.as_nanos() as u64).into()),
+            ("max_ns", (t.max.as_nanos() as u64).into()),
+        ])
+    });
+    Json::object([("timings", Json::Array(timings.collect()))])
+}
+
+pub fn from_json(json: &Json) -> Result<Vec<Timing>, String> {
+    let timings = json
+        .get("timings")
+        .and_then(Json::as_array)
+        .ok_or("expected a \"timings\" array")?;
+    timings
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let field = |key| {
+                t.get(key)
+                    .and_then(Json::as_f64)
+                    .filter(|n| *n >= 0.0)
+                    .ok_or_else(|| format!("timing {i}: expected a number {key:?}"))
+            };
+            let nanos = |key| field(key).map(|n| Duration::from_nanos(n as u64));
+            Ok(Timing {
+                day: field("day")? as u8,
+                part: field("part")? as u8,
+                runs: field("runs")? as usize,
+                min: nanos("min_ns")?,
+                median: nanos("median_ns")?,
+                max: nanos("max_ns")?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::*;
+    use std::fs;
+    #[test]
+    fn bench_example() {
+        let input = fs::read_to_string("input/example01").unwrap();
+        let timing = bench_part(crate::day(1).unwrap(), 2, &input, 1, 4).unwrap();
+        assert_eq!((timing.day, timing.part, timing.runs), (1, 2, 4));
+        assert!(timing.min <= timing.median && timing.median <= timing.max);
+        assert!(bench_part(crate::day(1).unwrap(), 3, &input, 0, 1).is_err());
+    }
+    #[test]
+    fn baseline_round_trip() {
+        let ms = Duration::from_millis;
+        let timing = Timing {
+            day: 16,
+            part: 1,
+            runs: 3,
+            min: ms(250),
+            median: ms(270),
+            max: ms(300),
+        };
+        let text = to_json(std::slice::from_ref(&timing)).to_string();
+        let baseline = from_json(&Json::parse(&text).unwrap()).unwrap();
+        assert_eq!(baseline, std::slice::from_ref(&timing));
+        let slower = Timing {
+            median: ms(324),
+            ..timing
+        };
+        assert!((change(&slower, &baseline).unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(change(&Timing { part: 2, ..slower }, &baseline), None);
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The clock and the cancel flag are only looked at every this many nodes.
+const CHECK_EVERY: u64 = 1024;
+
+/// Limits on how long a search may run: a deadline, a number of nodes, a flag that
+/// can be raised from another thread. Searches `spend` it once per node and stop
+/// when it runs out.
+#[derive(Debug, Default)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    node_limit: Option<u64>,
+    cancel: Option<Arc<AtomicBool>>,
+    nodes: AtomicU64,
+    exhausted: AtomicBool,
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+    pub fn with_node_limit(self, nodes: u64) -> Self {
+        Self {
+            node_limit: Some(nodes),
+            ..self
+        }
+    }
+    /// Runs out once `cancel` is set.
+    pub fn with_cancel(self, cancel: Arc<AtomicBool>) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+    /// Counts one node. False once the budget has run out, and from then on.
+    pub fn spend(&self) -> bool {
+        if self.exhausted.load(Relaxed) {
+            return false;
+        }
+        let nodes = self.nodes.fetch_add(1, Relaxed) + 1;
+        let out = self.node_limit.is_some_and(|limit| nodes > limit)
+            || (nodes.is_multiple_of(CHECK_EVERY)
+                && (self.deadline.is_some_and(|d| Instant::now() >= d)
+                    || self.cancel.as_ref().is_some_and(|c| c.load(Relaxed))));
+        if out {
+            self.exhausted.store(true, Relaxed);
+        }
+        !out
+    }
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted.load(Relaxed)
+    }
+    /// Nodes spent so far.
+    pub fn nodes(&self) -> u64 {
+        self.nodes.load(Relaxed)
+    }
+    /// Tags `value` with whether the budget lasted.
+    pub fn finish<T>(&self, value: T) -> Bounded<T> {
+        Bounded {
+            value,
+            complete: !self.is_exhausted(),
+        }
+    }
+}
+
+/// The result of a search that may have been cut short. When it was, `value` is only
+/// the best found so far, e.g. a lower bound for a maximum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bounded<T> {
+    pub value: T,
+    pub complete: bool,
+}
+
+impl<T> Bounded<T> {
+    pub fn complete(value: T) -> Self {
+        Self {
+            value,
+            complete: true,
+        }
+    }
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Bounded<U> {
+        Bounded {
+            value: f(self.value),
+            complete: self.complete,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::budget::*;
+    #[test]
+    fn limits() {
+        let budget = Budget::unlimited().with_node_limit(3);
+        assert_eq!((0..5).filter(|_| budget.spend()).count(), 3);
+        assert_eq!(
+            budget.finish(7),
+            Bounded {
+                value: 7,
+                complete: false
+            }
+        );
+        let cancel = Arc::new(AtomicBool::new(false));
+        let budget = Budget::unlimited().with_cancel(cancel.clone());
+        assert!((0..2 * CHECK_EVERY).all(|_| budget.spend()));
+        cancel.store(true, Relaxed);
+        assert!(!(0..CHECK_EVERY).all(|_| budget.spend()));
+        let budget = Budget::unlimited().with_timeout(Duration::ZERO);
+        assert!(!(0..CHECK_EVERY).all(|_| budget.spend()));
+        assert!(Budget::unlimited().finish(()).complete);
+    }
+}
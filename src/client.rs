@@ -0,0 +1,463 @@
+use crate::json::Json;
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The puzzle year, part of every path on the server.
+const YEAR: u16 = 2022;
+/// Where submission responses are kept, relative to the crate root.
+pub const DEFAULT_CACHE_DIR: &str = "input/cache";
+/// Time to leave between requests, so as not to hammer the server.
+pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What the server said about a submitted answer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Right,
+    /// With whether the answer was too high or too low, if the server said.
+    Wrong(Option<Hint>),
+    /// Submitted too soon after a wrong answer, with how long is left to wait if given.
+    TooSoon(Option<Duration>),
+    /// The part is already solved, or not unlocked yet.
+    WrongLevel,
+    /// The text of any other response.
+    Unknown(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hint {
+    TooHigh,
+    TooLow,
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text
+}
+
+/// `1m 5s` and the like.
+fn parse_wait(text: &str) -> Option<Duration> {
+    let mut secs = 0;
+    for part in text.split_whitespace() {
+        let (n, unit) = part.split_at(part.find(|c: char| !c.is_ascii_digit())?);
+        let n: u64 = n.parse().ok()?;
+        secs += match unit {
+            "h" => n * 3600,
+            "m" => n * 60,
+            "s" => n,
+            _ => return None,
+        };
+    }
+    Some(Duration::from_secs(secs))
+}
+
+impl Verdict {
+    /// Reads the verdict from the page the server answers a submission with.
+    pub fn parse(body: &str) -> Self {
+        let text = strip_tags(body);
+        if text.contains("That's the right answer") {
+            Verdict::Right
+        } else if text.contains("That's not the right answer") {
+            let hint = if text.contains("too high") {
+                Some(Hint::TooHigh)
+            } else if text.contains("too low") {
+                Some(Hint::TooLow)
+            } else {
+                None
+            };
+            Verdict::Wrong(hint)
+        } else if text.contains("You gave an answer too recently") {
+            let wait = text
+                .split_once("You have ")
+                .and_then(|(_, rest)| rest.split_once(" left to wait"))
+                .and_then(|(wait, _)| parse_wait(wait));
+            Verdict::TooSoon(wait)
+        } else if text.contains("You don't seem to be solving the right level") {
+            Verdict::WrongLevel
+        } else {
+            Verdict::Unknown(text.trim().to_string())
+        }
+    }
+    /// Whether asking again with the same answer would get the same verdict.
+    fn is_final(&self) -> bool {
+        matches!(self, Verdict::Right | Verdict::Wrong(_))
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Right => write!(f, "right"),
+            Verdict::Wrong(None) => write!(f, "wrong"),
+            Verdict::Wrong(Some(Hint::TooHigh)) => write!(f, "wrong, too high"),
+            Verdict::Wrong(Some(Hint::TooLow)) => write!(f, "wrong, too low"),
+            Verdict::TooSoon(None) => write!(f, "too soon"),
+            Verdict::TooSoon(Some(wait)) => write!(f, "too soon, wait {wait:?}"),
+            Verdict::WrongLevel => write!(f, "already solved or not unlocked"),
+            Verdict::Unknown(text) => write!(f, "unknown response: {text}"),
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or("truncated chunk")?;
+        let size = String::from_utf8_lossy(&body[..line_end]);
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let size =
+            usize::from_str_radix(size, 16).map_err(|_| format!("bad chunk size: {size}"))?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let chunk = body
+            .get(line_end + 2..line_end + 2 + size)
+            .ok_or("truncated chunk")?;
+        decoded.extend_from_slice(chunk);
+        body = body.get(line_end + 4 + size..).ok_or("truncated chunk")?;
+    }
+}
+
+fn parse_response(raw: &[u8]) -> Result<Response, String> {
+    let header_end = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or("response without a header end")?;
+    let head = String::from_utf8_lossy(&raw[..header_end]);
+    let mut lines = head.lines();
+    let status_line = lines.next().unwrap_or_default();
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| format!("bad status line: {status_line:?}"))?;
+    let chunked = lines.any(|l| {
+        l.split_once(':').is_some_and(|(name, value)| {
+            name.eq_ignore_ascii_case("transfer-encoding") && value.trim() == "chunked"
+        })
+    });
+    let body = &raw[header_end + 4..];
+    let body = if chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_vec()
+    };
+    let body = String::from_utf8(body).map_err(|_| "response body isn't UTF-8")?;
+    Ok(Response { status, body })
+}
+
+fn form_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => (b as char).to_string(),
+            b => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Talks to a puzzle server over plain HTTP, one connection per request.
+pub struct Client {
+    /// `host:port`, for connecting and the Host header.
+    host: String,
+    /// Path the server's routes are under, without a trailing '/'.
+    prefix: String,
+    session: String,
+    cache_dir: PathBuf,
+    min_interval: Duration,
+    last_request: Option<Instant>,
+}
+
+impl Client {
+    /// `base_url` is like `http://localhost:8080` or `http://host/some/prefix`.
+    pub fn new(base_url: &str, session: &str) -> Result<Self, String> {
+        let rest = base_url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("only http:// base URLs are supported: {base_url}"))?;
+        let (host, prefix) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        if host.is_empty() {
+            return Err(format!("no host in {base_url}"));
+        }
+        let host = match host.contains(':') {
+            true => host.to_string(),
+            false => format!("{host}:80"),
+        };
+        Ok(Self {
+            host,
+            prefix: prefix.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            cache_dir: PathBuf::from(DEFAULT_CACHE_DIR),
+            min_interval: DEFAULT_MIN_INTERVAL,
+            last_request: None,
+        })
+    }
+    pub fn with_cache_dir(self, cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
+            ..self
+        }
+    }
+    pub fn with_min_interval(self, min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            ..self
+        }
+    }
+    /// Sends one request, first waiting out what's left of the interval since the last.
+    fn request(
+        &mut self,
+        method: &str,
+        path: &str,
+        form: Option<&str>,
+    ) -> Result<Response, String> {
+        if let Some(last) = self.last_request {
+            thread::sleep(self.min_interval.saturating_sub(last.elapsed()));
+        }
+        self.last_request = Some(Instant::now());
+        let path = format!("{}{path}", self.prefix);
+        let fail = |e: std::io::Error| format!("{method} {path}: {e}");
+        let mut stream = TcpStream::connect(&self.host).map_err(fail)?;
+        stream
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .map_err(fail)?;
+        let mut request = format!(
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\nCookie: session={}\r\n\
+             User-Agent: advent_of_code_2022 client\r\nConnection: close\r\n",
+            self.host, self.session
+        );
+        if let Some(form) = form {
+            request += "Content-Type: application/x-www-form-urlencoded\r\n";
+            request += &format!("Content-Length: {}\r\n\r\n{form}", form.len());
+        } else {
+            request += "\r\n";
+        }
+        stream.write_all(request.as_bytes()).map_err(fail)?;
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).map_err(fail)?;
+        let response = parse_response(&raw).map_err(|e| format!("{method} {path}: {e}"))?;
+        if response.status != 200 {
+            let first_line = response.body.lines().next().unwrap_or_default();
+            return Err(format!("{method} {path}: {} {first_line}", response.status));
+        }
+        Ok(response)
+    }
+    /// Downloads the input for `day` to `path`, unless it's there already.
+    /// Returns whether it downloaded.
+    pub fn fetch_input(&mut self, day: u8, path: &Path) -> Result<bool, String> {
+        if path.exists() {
+            return Ok(false);
+        }
+        let response = self.request("GET", &format!("/{YEAR}/day/{day}/input"), None)?;
+        fs::write(path, response.body).map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(true)
+    }
+    /// What cached submissions are kept under: the server, and the session hashed so
+    /// it isn't written down.
+    fn cache_key(&self) -> (String, String) {
+        // FNV-1a, which unlike std's hashers is the same on every build.
+        let hash = self
+            .session
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+                (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+            });
+        (
+            format!("http://{}{}", self.host, self.prefix),
+            format!("{hash:016x}"),
+        )
+    }
+    fn cache_path(&self) -> PathBuf {
+        self.cache_dir.join("submissions.jsonl")
+    }
+    /// The response to an earlier submission of the same answer, to the same server with
+    /// the same session, if the verdict was final.
+    fn cached(&self, day: u8, part: u8, answer: &str) -> Option<Verdict> {
+        let text = fs::read_to_string(self.cache_path()).ok()?;
+        let (server, session) = self.cache_key();
+        text.lines()
+            .filter_map(|line| Json::parse(line).ok())
+            .find(|entry| {
+                entry.get("server").and_then(Json::as_str) == Some(&server)
+                    && entry.get("session").and_then(Json::as_str) == Some(&session)
+                    && entry.get("day").and_then(Json::as_f64) == Some(day as f64)
+                    && entry.get("part").and_then(Json::as_f64) == Some(part as f64)
+                    && entry.get("answer").and_then(Json::as_str) == Some(answer)
+            })
+            .and_then(|entry| Some(Verdict::parse(entry.get("response")?.as_str()?)))
+    }
+    fn cache(&self, day: u8, part: u8, answer: &str, response: &str) -> Result<(), String> {
+        let path = self.cache_path();
+        let fail = |e: std::io::Error| format!("{}: {e}", path.display());
+        fs::create_dir_all(&self.cache_dir).map_err(fail)?;
+        let (server, session) = self.cache_key();
+        let entry = Json::object([
+            ("server", server.into()),
+            ("session", session.into()),
+            ("day", day.into()),
+            ("part", part.into()),
+            ("answer", answer.into()),
+            ("response", response.into()),
+        ]);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(fail)?;
+        writeln!(file, "{entry}").map_err(fail)
+    }
+    /// Submits `answer` for a part. An answer already known to be right or wrong is
+    /// answered from the cache, without asking the server again.
+    pub fn submit(&mut self, day: u8, part: u8, answer: &str) -> Result<Verdict, String> {
+        if let Some(verdict) = self.cached(day, part, answer) {
+            return Ok(verdict);
+        }
+        let form = format!("level={part}&answer={}", form_encode(answer));
+        let path = format!("/{YEAR}/day/{day}/answer");
+        let response = self.request("POST", &path, Some(&form))?;
+        let verdict = Verdict::parse(&response.body);
+        if verdict.is_final() {
+            self.cache(day, part, answer, &response.body)?;
+        }
+        Ok(verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::*;
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+    };
+    /// Answers one connection per response with it, then returns the requests it got.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}/aoc/", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(n) = line.strip_prefix("Content-Length: ") {
+                        length = n.trim().parse().unwrap();
+                    }
+                    request += &line;
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut form = vec![0; length];
+                reader.read_exact(&mut form).unwrap();
+                request += &String::from_utf8(form).unwrap();
+                requests.push(request);
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (base, server)
+    }
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    #[test]
+    fn fetch_and_submit() {
+        let (base, server) = serve(vec![
+            (200, "1\n2\n"),
+            (200, "<article><p>That's not the right answer; your answer is too high.</p></article>"),
+            (200, "<p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait.</p>"),
+            (200, "<p>That's the right answer!  You are one gold star closer.</p>"),
+            (404, "Not Found"),
+        ]);
+        let dir = temp_dir("client");
+        let interval = Duration::from_millis(50);
+        let mut client = Client::new(&base, "abc")
+            .unwrap()
+            .with_cache_dir(dir.join("cache"))
+            .with_min_interval(interval);
+        let start = Instant::now();
+        let input = dir.join("day01");
+        assert_eq!(client.fetch_input(1, &input), Ok(true));
+        assert_eq!(client.fetch_input(1, &input), Ok(false));
+        assert_eq!(fs::read_to_string(&input).unwrap(), "1\n2\n");
+        let too_high = Verdict::Wrong(Some(Hint::TooHigh));
+        assert_eq!(client.submit(1, 2, "4 2"), Ok(too_high.clone()));
+        // Cached, so this doesn't use up a response.
+        assert_eq!(client.submit(1, 2, "4 2"), Ok(too_high));
+        let wait = Duration::from_secs(65);
+        assert_eq!(client.submit(1, 2, "41"), Ok(Verdict::TooSoon(Some(wait))));
+        assert_eq!(client.submit(1, 2, "41"), Ok(Verdict::Right));
+        let other = |base: &str, session| {
+            Client::new(base, session)
+                .unwrap()
+                .with_cache_dir(dir.join("cache"))
+        };
+        assert!(other(&base, "abc").cached(1, 2, "41").is_some());
+        assert!(other(&base, "xyz").cached(1, 2, "41").is_none());
+        assert!(other("http://elsewhere/aoc", "abc")
+            .cached(1, 2, "41")
+            .is_none());
+        let missing = client.fetch_input(2, &dir.join("day02")).unwrap_err();
+        assert_eq!(missing, "GET /aoc/2022/day/2/input: 404 Not Found");
+        assert!(start.elapsed() >= 4 * interval);
+        let requests = server.join().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(requests[0].starts_with("GET /aoc/2022/day/1/input HTTP/1.1\r\n"));
+        assert!(requests[0].contains("\r\nCookie: session=abc\r\n"));
+        assert!(requests[1].starts_with("POST /aoc/2022/day/1/answer HTTP/1.1\r\n"));
+        assert!(requests[1].ends_with("\r\n\r\nlevel=2&answer=4%202"));
+    }
+    #[test]
+    fn responses() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nThat\r\n\
+                    13\r\n's the right answer\r\n0\r\n\r\n";
+        let response = parse_response(raw).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(Verdict::parse(&response.body), Verdict::Right);
+        let level = "You don't seem to be solving the right level.  Did you already complete it?";
+        assert_eq!(Verdict::parse(level), Verdict::WrongLevel);
+        assert_eq!(
+            Verdict::parse("<p>That's not the right answer.</p>"),
+            Verdict::Wrong(None)
+        );
+        assert_eq!(
+            Verdict::parse("<p>Teapot</p>"),
+            Verdict::Unknown("Teapot".to_string())
+        );
+        assert!(Client::new("https://example.com", "").is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A repeating stretch in a simulation: after `prefix` steps, the state comes back
+/// every `period` steps, and the measured value grows by `delta()` each time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+    /// Measured value after 0, 1, ..., `prefix + period` steps.
+    pub values: Vec<i64>,
+}
+
+impl Cycle {
+    /// How much the measured value changes over one period.
+    pub fn delta(&self) -> i64 {
+        self.values[self.prefix + self.period] - self.values[self.prefix]
+    }
+    /// Measured value after any number of steps.
+    pub fn value_at(&self, steps: u64) -> i64 {
+        if let Some(&v) = self.values.get(steps as usize) {
+            return v;
+        }
+        let after_prefix = steps - self.prefix as u64;
+        let cycles = after_prefix / self.period as u64;
+        let rest = (after_prefix % self.period as u64) as usize;
+        self.values[self.prefix + rest] + cycles as i64 * self.delta()
+    }
+}
+
+/// Steps `state` until its fingerprint repeats, recording `measure` after every step.
+/// The fingerprint must capture everything that decides the future steps, or the
+/// "cycle" found may not be one. Gives up after `max_steps`.
+pub fn find<S, K: Hash + Eq>(
+    mut state: S,
+    mut step: impl FnMut(&mut S),
+    mut fingerprint: impl FnMut(&S) -> K,
+    mut measure: impl FnMut(&S) -> i64,
+    max_steps: usize,
+) -> Option<Cycle> {
+    let mut seen = HashMap::new();
+    let mut values = Vec::new();
+    for i in 0..=max_steps {
+        values.push(measure(&state));
+        if let Some(first) = seen.insert(fingerprint(&state), i) {
+            return Some(Cycle {
+                prefix: first,
+                period: i - first,
+                values,
+            });
+        }
+        step(&mut state);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cycle::*;
+    #[test]
+    fn extrapolate() {
+        // 0, 1, 2, then 3, 4, 5 repeating mod 3 from 3 on; value is the sum so far.
+        let step = |(n, sum): &mut (u32, i64)| {
+            *n = if *n < 5 { *n + 1 } else { 3 };
+            *sum += *n as i64;
+        };
+        let cycle = find((0, 0), step, |s| s.0, |s| s.1, 100).unwrap();
+        assert_eq!((cycle.prefix, cycle.period, cycle.delta()), (3, 3, 12));
+        let mut state = (0, 0);
+        for steps in 0..20 {
+            assert_eq!(cycle.value_at(steps), state.1);
+            step(&mut state);
+        }
+        assert_eq!(find(0, |n| *n += 1, |n| *n, |_| 0, 100), None);
+    }
+}
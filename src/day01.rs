@@ -1,41 +1,284 @@
-fn block_sums(input: &str) -> impl Iterator<Item = i32> + '_ {
-    input.split("\n\n").map(|block| {
-        block
-            .lines()
-            .map(|l| l.parse::<i32>().unwrap())
-            .sum::<i32>()
-    })
+use crate::{
+    error::{Error, Result},
+    parse,
+    solution::Solution,
+};
+use std::{cmp::Reverse, collections::BinaryHeap, io::BufRead, num::NonZeroU32};
+
+const DAY: u8 = 1;
+
+/// Calls `f` with each item's elf index and calories, reading one line at a time.
+fn read_items(input: &mut dyn BufRead, mut f: impl FnMut(usize, i64)) -> Result<()> {
+    // Blocks are separated by exactly one blank line.
+    let mut after_blank = true;
+    let lines = parse::read_lines(DAY, input, move |mut l| {
+        if l.is_done() && !after_blank {
+            after_blank = true;
+            return Ok(None);
+        }
+        after_blank = false;
+        let calories = l.int::<i64>()?;
+        l.end()?;
+        Ok(Some(calories))
+    });
+    let mut elf = 0;
+    let mut in_block = false;
+    for calories in lines {
+        match calories? {
+            Some(calories) => {
+                f(elf, calories);
+                in_block = true;
+            }
+            None => {
+                elf += usize::from(in_block);
+                in_block = false;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Calls `f` with each elf's index and calorie sum.
+fn read_block_sums(input: &mut dyn BufRead, mut f: impl FnMut(usize, i64)) -> Result<()> {
+    let mut current = None;
+    read_items(input, |elf, calories| match &mut current {
+        Some((i, sum)) if *i == elf => *sum += calories,
+        _ => {
+            if let Some((i, sum)) = current.replace((elf, calories)) {
+                f(i, sum);
+            }
+        }
+    })?;
+    current.into_iter().for_each(|(i, sum)| f(i, sum));
+    Ok(())
+}
+
+/// The `k` largest (index, calories) elf sums, most first, in one pass keeping only
+/// `k` of them. Unlike `top_k`, it never returns more than `k`: of elves tied at the
+/// cutoff, the earlier ones are kept.
+pub fn top_k_read(input: &mut dyn BufRead, k: usize) -> Result<Vec<(usize, i64)>> {
+    // A min-heap of the best so far, whose smallest is the first to go.
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    read_block_sums(input, |index, sum| {
+        heap.push(Reverse((sum, Reverse(index))));
+        if heap.len() > k {
+            heap.pop();
+        }
+    })?;
+    Ok(heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((sum, Reverse(index)))| (index, sum))
+        .collect())
+}
+
+/// One elf's inventory, in input order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Elf {
+    /// 0-based position in the input.
+    pub index: usize,
+    pub items: Vec<i64>,
+}
+
+impl Elf {
+    pub fn calories(&self) -> i64 {
+        self.items.iter().sum()
+    }
+}
+
+pub fn elves(input: &str) -> Result<Vec<Elf>> {
+    let mut elves: Vec<Elf> = Vec::new();
+    read_items(&mut input.as_bytes(), |index, calories| {
+        match elves.last_mut().filter(|e| e.index == index) {
+            Some(elf) => elf.items.push(calories),
+            None => elves.push(Elf {
+                index,
+                items: vec![calories],
+            }),
+        }
+    })?;
+    Ok(elves)
+}
+
+/// The `k` elves carrying the most calories, most first, and any more tied with the
+/// last of them. Ties are in input order.
+pub fn top_k(elves: &[Elf], k: usize) -> Vec<&Elf> {
+    let mut sorted: Vec<_> = elves.iter().collect();
+    sorted.sort_by_key(|e| (Reverse(e.calories()), e.index));
+    let Some(last) = k.checked_sub(1).and_then(|i| sorted.get(i)) else {
+        return if k == 0 { Vec::new() } else { sorted };
+    };
+    let cutoff = last.calories();
+    sorted.retain(|e| e.calories() >= cutoff);
+    sorted
+}
+
+/// Summary statistics of the elves' calorie totals.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    sorted: Vec<i64>,
+}
+
+impl Stats {
+    pub fn new(elves: &[Elf]) -> Self {
+        let mut sorted: Vec<_> = elves.iter().map(Elf::calories).collect();
+        sorted.sort_unstable();
+        Self { sorted }
+    }
+    pub fn mean(&self) -> Option<f64> {
+        let total: i128 = self.sorted.iter().map(|&c| i128::from(c)).sum();
+        (!self.sorted.is_empty()).then(|| total as f64 / self.sorted.len() as f64)
+    }
+    /// The middle total, or the mean of the two middle ones.
+    pub fn median(&self) -> Option<f64> {
+        let n = self.sorted.len();
+        match n {
+            0 => None,
+            _ if n.is_multiple_of(2) => {
+                Some((self.sorted[n / 2 - 1] as f64 + self.sorted[n / 2] as f64) / 2.0)
+            }
+            _ => Some(self.sorted[n / 2] as f64),
+        }
+    }
+    /// The smallest total that at least `p` percent of the elves carry at most
+    /// (nearest rank). `p` is clamped to 0..=100.
+    pub fn percentile(&self, p: f64) -> Option<i64> {
+        let n = self.sorted.len();
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * n as f64).ceil() as usize;
+        self.sorted.get(rank.clamp(1, n.max(1)) - 1).copied()
+    }
+    /// The number of elves in each bucket of `width` calories, as (bucket start,
+    /// count), from the lowest total's bucket to the highest's, empty ones included.
+    pub fn histogram(&self, width: NonZeroU32) -> Vec<(i64, usize)> {
+        let width = i64::from(width.get());
+        let (Some(&min), Some(&max)) = (self.sorted.first(), self.sorted.last()) else {
+            return Vec::new();
+        };
+        let bucket = |c: i64| c.div_euclid(width);
+        let mut counts: Vec<_> = (bucket(min)..=bucket(max))
+            .map(|b| (b * width, 0))
+            .collect();
+        for &c in &self.sorted {
+            counts[(bucket(c) - bucket(min)) as usize].1 += 1;
+        }
+        counts
+    }
 }
 
-pub fn part_1(input: &str) -> i32 {
-    block_sums(input).max().unwrap()
+pub fn part_1_read(input: &mut dyn BufRead) -> Result<i64> {
+    let mut max = None;
+    read_block_sums(input, |_, sum| max = max.max(Some(sum)))?;
+    max.ok_or_else(|| Error::no_answer(DAY, "no elves"))
+}
+
+pub fn part_2_read(input: &mut dyn BufRead) -> Result<i64> {
+    let top = top_k_read(input, 3)?;
+    if top.len() < 3 {
+        return Err(Error::no_answer(DAY, "fewer than three elves"));
+    }
+    Ok(top.iter().map(|(_, sum)| sum).sum())
 }
 
-pub fn part_2(input: &str) -> i32 {
-    let mut calorie_sums: Vec<_> = block_sums(input).collect();
-    let len = calorie_sums.len();
-    calorie_sums.select_nth_unstable(len - 3);
-    calorie_sums[(len - 3)..].iter().sum::<i32>()
+pub fn part_1(input: &str) -> Result<i64> {
+    part_1_read(&mut input.as_bytes())
+}
+
+pub fn part_2(input: &str) -> Result<i64> {
+    part_2_read(&mut input.as_bytes())
+}
+
+pub struct Day01;
+impl Solution for Day01 {
+    type Answer1 = i64;
+    type Answer2 = i64;
+    fn part_1(input: &str) -> Result<i64> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<i64> {
+        part_2(input)
+    }
+    fn part_1_read(input: &mut dyn BufRead) -> Result<i64> {
+        part_1_read(input)
+    }
+    fn part_2_read(input: &mut dyn BufRead) -> Result<i64> {
+        part_2_read(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day01::*;
-    use std::fs;
+    use crate::manifest::{check, check_read};
 
     #[test]
     fn day01_part1() {
-        let input = fs::read_to_string("input/day01").unwrap();
-        assert_eq!(part_1(&input), 69177);
+        check(DAY, 1, "day01");
     }
     #[test]
     fn example01_part2() {
-        let input = fs::read_to_string("input/example01").unwrap();
-        assert_eq!(part_2(&input), 45000);
+        check(DAY, 2, "example01");
     }
     #[test]
     fn day01_part2() {
-        let input = fs::read_to_string("input/day01").unwrap();
-        assert_eq!(part_2(&input), 207456);
+        check(DAY, 2, "day01");
+    }
+    #[test]
+    fn day01_read() {
+        check_read(DAY, 1, "day01");
+        check_read(DAY, 2, "day01");
+    }
+    #[test]
+    fn top_and_stats() {
+        let input = std::fs::read_to_string("input/example01").unwrap();
+        let elves = elves(&input).unwrap();
+        assert_eq!(elves.len(), 5);
+        assert_eq!(elves[2].items, [5000, 6000]);
+        let top = |k| top_k(&elves, k).iter().map(|e| e.index).collect::<Vec<_>>();
+        assert_eq!(top(3), [3, 2, 4]);
+        assert_eq!(top(0), []);
+        assert_eq!(top(9), [3, 2, 4, 0, 1]);
+        // 1 and 2 tie for second place.
+        let tied = crate::day01::elves("5\n\n3\n\n1\n2\n\n1\n").unwrap();
+        assert_eq!(top_k(&tied, 2).len(), 3);
+        let stats = Stats::new(&elves);
+        assert_eq!(stats.mean(), Some(11000.0));
+        assert_eq!(stats.median(), Some(10000.0));
+        assert_eq!(stats.percentile(50.0), Some(10000));
+        assert_eq!(stats.percentile(100.0), Some(24000));
+        assert_eq!(stats.percentile(0.0), Some(4000));
+        assert_eq!(
+            stats.histogram(NonZeroU32::new(10000).unwrap()),
+            [(0, 2), (10000, 2), (20000, 1)]
+        );
+        let empty = Stats::new(&[]);
+        assert_eq!((empty.mean(), empty.percentile(50.0)), (None, None));
+    }
+    #[test]
+    fn streaming_top_k() {
+        for seed in 0..5 {
+            let input = crate::generator::generate(DAY, 200, seed).unwrap();
+            let elves = elves(&input).unwrap();
+            for k in [0, 1, 3, 50, 300] {
+                let mut expected: Vec<_> = top_k(&elves, k)
+                    .iter()
+                    .map(|e| (e.index, e.calories()))
+                    .collect();
+                expected.truncate(k);
+                assert_eq!(top_k_read(&mut input.as_bytes(), k).unwrap(), expected);
+            }
+        }
+        assert!(part_2("1\n\n2\n").is_err());
+        // More than fits in an i32.
+        let big = "2000000000\n2000000000\n\n1\n\n2\n";
+        assert_eq!(part_1(big), Ok(4_000_000_000));
+        assert_eq!(part_2(big), Ok(4_000_000_003));
+    }
+    #[test]
+    fn blank_lines() {
+        let err = part_1("1\n\n\n2\n").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "day 01, line 3, column 1: expected a number, found nothing"
+        );
     }
 }
@@ -1,107 +1,400 @@
-enum Outcome {
-    Win,
+use crate::{error::Result, parse, solution::Solution};
+use std::{fmt, io::BufRead};
+
+const DAY: u8 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
     Loss,
     Tie,
+    Win,
+}
+
+/// One shape of a game, with the symbols it's written as in each column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shape {
+    pub opponent: char,
+    pub me: char,
+    pub score: u32,
+}
+
+/// How the second column of the guide is read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    /// The shape to play, as in part 1.
+    Shape,
+    /// The outcome to get, as in part 2.
+    Outcome,
 }
-impl Outcome {
-    fn score(&self) -> u32 {
-        match self {
-            Outcome::Loss => 0,
-            Outcome::Tie => 3,
-            Outcome::Win => 6,
+
+/// A cyclic dominance game with an odd number of shapes: each one beats the half of
+/// the others just before it in the cycle, wrapping around, and loses to the rest.
+#[derive(Clone, Debug)]
+pub struct Game {
+    shapes: Vec<Shape>,
+    /// Symbol and score of a loss, a tie and a win.
+    outcomes: [(char, u32); 3],
+}
+
+fn one_of(symbols: impl Iterator<Item = char>) -> String {
+    let symbols: Vec<_> = symbols.map(String::from).collect();
+    match symbols.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {last}", rest.join(", ")),
+        None => String::new(),
+    }
+}
+
+impl Game {
+    pub fn new(
+        shapes: Vec<Shape>,
+        outcomes: [(char, u32); 3],
+    ) -> std::result::Result<Self, String> {
+        if shapes.len().is_multiple_of(2) {
+            return Err(format!(
+                "{} shapes, the game needs an odd number",
+                shapes.len()
+            ));
+        }
+        let columns: [Vec<char>; 3] = [
+            shapes.iter().map(|s| s.opponent).collect(),
+            shapes.iter().map(|s| s.me).collect(),
+            outcomes.iter().map(|o| o.0).collect(),
+        ];
+        for symbols in columns {
+            if let Some(c) = symbols
+                .iter()
+                .find(|c| symbols.iter().filter(|d| d == c).count() > 1)
+            {
+                return Err(format!("symbol {c} is used twice"));
+            }
         }
+        Ok(Self { shapes, outcomes })
+    }
+    /// Rock, paper and scissors, written A/B/C and X/Y/Z.
+    pub fn rock_paper_scissors() -> Self {
+        Self::cyclic("ABC", "XYZ", &[1, 2, 3])
+    }
+    /// Rock, Spock, paper, lizard and scissors in cycle order, written A to E and V to Z.
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        Self::cyclic("ABCDE", "VWXYZ", &[1, 5, 2, 4, 3])
+    }
+    fn cyclic(opponent: &str, me: &str, scores: &[u32]) -> Self {
+        let shapes = opponent
+            .chars()
+            .zip(me.chars())
+            .zip(scores)
+            .map(|((opponent, me), &score)| Shape {
+                opponent,
+                me,
+                score,
+            })
+            .collect();
+        Self::new(shapes, [('X', 0), ('Y', 3), ('Z', 6)]).unwrap()
     }
-    fn from_str(s: &str) -> Self {
-        match s {
-            "X" => Self::Loss,
-            "Y" => Self::Tie,
-            "Z" => Self::Win,
-            _ => panic!(),
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+    /// The outcome for `me` playing shape `me` against shape `other`, both indices.
+    pub fn play(&self, me: usize, other: usize) -> Outcome {
+        let n = self.shapes.len();
+        match (me + n - other) % n {
+            0 => Outcome::Tie,
+            d if d <= n / 2 => Outcome::Win,
+            _ => Outcome::Loss,
         }
     }
+    /// The best scoring shape that gets `outcome` against `other`, the first if tied.
+    pub fn respond(&self, other: usize, outcome: Outcome) -> usize {
+        (0..self.shapes.len())
+            .filter(|&me| self.play(me, other) == outcome)
+            .min_by_key(|&me| std::cmp::Reverse(self.shapes[me].score))
+            .expect("every outcome is possible against every shape")
+    }
+    pub fn outcome_score(&self, outcome: Outcome) -> u32 {
+        self.outcomes[outcome as usize].1
+    }
+    /// Our score for a round.
+    pub fn score(&self, me: usize, other: usize) -> u32 {
+        self.shapes[me].score + self.outcome_score(self.play(me, other))
+    }
+    /// The rounds of a guide as (opponent's shape, our shape).
+    pub fn read_rounds<'a>(
+        &'a self,
+        input: &'a mut dyn BufRead,
+        column: Column,
+    ) -> impl Iterator<Item = Result<(usize, usize)>> + 'a {
+        let opponent = one_of(self.shapes.iter().map(|s| s.opponent));
+        let second = match column {
+            Column::Shape => one_of(self.shapes.iter().map(|s| s.me)),
+            Column::Outcome => one_of(self.outcomes.iter().map(|o| o.0)),
+        };
+        parse::read_lines(DAY, input, move |mut l| {
+            let other = l.char_map(&opponent, |c| {
+                self.shapes.iter().position(|s| s.opponent == c)
+            })?;
+            l.tag(" ")?;
+            let me = match column {
+                Column::Shape => {
+                    l.char_map(&second, |c| self.shapes.iter().position(|s| s.me == c))?
+                }
+                Column::Outcome => {
+                    let outcome = l.char_map(&second, |c| {
+                        let i = self.outcomes.iter().position(|o| o.0 == c)?;
+                        Some([Outcome::Loss, Outcome::Tie, Outcome::Win][i])
+                    })?;
+                    self.respond(other, outcome)
+                }
+            };
+            l.end()?;
+            Ok((other, me))
+        })
+    }
+    /// Our total score following the guide.
+    pub fn score_read(&self, input: &mut dyn BufRead, column: Column) -> Result<u32> {
+        let mut score = 0;
+        for round in self.read_rounds(input, column) {
+            let (other, me) = round?;
+            score += self.score(me, other);
+        }
+        Ok(score)
+    }
+}
+
+/// Our shape in a round, and how it went.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Play {
+    pub me: usize,
+    pub outcome: Outcome,
+    pub score: u32,
+}
+
+/// One round of a guide, played under both readings of its second column and with
+/// the best shape.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Round {
+    pub opponent: usize,
+    pub as_shape: Play,
+    pub as_outcome: Play,
+    pub best: Play,
 }
-#[derive(PartialEq, Debug, Clone)]
-enum Rps {
-    Rock,
-    Paper,
-    Scissors,
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub wins: usize,
+    pub ties: usize,
+    pub losses: usize,
+    pub score: u32,
 }
-impl Rps {
-    fn score(&self) -> u32 {
-        match self {
-            Rps::Rock => 1,
-            Rps::Paper => 2,
-            Rps::Scissors => 3,
+
+impl Totals {
+    fn add(&mut self, play: Play) {
+        match play.outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Tie => self.ties += 1,
+            Outcome::Loss => self.losses += 1,
         }
+        self.score += play.score;
     }
-    fn from_str(s: &str) -> Self {
-        match s {
-            "A" | "X" => Self::Rock,
-            "B" | "Y" => Self::Paper,
-            "C" | "Z" => Self::Scissors,
-            _ => panic!(),
+}
+
+/// What a guide is worth: each round, and totals for following it either way and
+/// for choosing every shape freely.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub rounds: Vec<Round>,
+    pub as_shape: Totals,
+    pub as_outcome: Totals,
+    pub best: Totals,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<10} {:>6} {:>6} {:>6} {:>8}",
+            "", "wins", "ties", "losses", "score"
+        )?;
+        for (name, t) in [
+            ("as shape", self.as_shape),
+            ("as outcome", self.as_outcome),
+            ("best", self.best),
+        ] {
+            writeln!(
+                f,
+                "{name:<10} {:>6} {:>6} {:>6} {:>8}",
+                t.wins, t.ties, t.losses, t.score
+            )?;
         }
+        Ok(())
     }
-    fn from_outcome(other: &Self, outcome: &Outcome) -> Self {
-        match outcome {
-            Outcome::Win => match other {
-                Rps::Rock => Rps::Paper,
-                Rps::Paper => Rps::Scissors,
-                Rps::Scissors => Rps::Rock,
-            },
-            Outcome::Loss => match other {
-                Rps::Rock => Rps::Scissors,
-                Rps::Paper => Rps::Rock,
-                Rps::Scissors => Rps::Paper,
-            },
-            Outcome::Tie => other.clone(),
+}
+
+impl Game {
+    fn play_as(&self, me: usize, other: usize) -> Play {
+        Play {
+            me,
+            outcome: self.play(me, other),
+            score: self.score(me, other),
         }
     }
-    fn play(&self, other: &Self) -> Outcome {
-        if self == other {
-            return Outcome::Tie;
-        }
-        match (&self, &other) {
-            (Rps::Rock, Rps::Scissors) | (Rps::Paper, Rps::Rock) | (Rps::Scissors, Rps::Paper) => {
-                Outcome::Win
-            }
-            _ => Outcome::Loss,
+    /// Plays the guide both ways and with the best shape each round. The second
+    /// column has to make sense both ways.
+    pub fn analyze(&self, input: &str) -> Result<Report> {
+        let as_shape: Vec<_> = self
+            .read_rounds(&mut input.as_bytes(), Column::Shape)
+            .collect::<Result<_>>()?;
+        let as_outcome: Vec<_> = self
+            .read_rounds(&mut input.as_bytes(), Column::Outcome)
+            .collect::<Result<_>>()?;
+        let mut report = Report {
+            rounds: Vec::new(),
+            as_shape: Totals::default(),
+            as_outcome: Totals::default(),
+            best: Totals::default(),
+        };
+        for ((other, me), (_, for_outcome)) in as_shape.into_iter().zip(as_outcome) {
+            let best = (0..self.shapes.len())
+                .min_by_key(|&me| std::cmp::Reverse(self.score(me, other)))
+                .expect("a game has shapes");
+            let round = Round {
+                opponent: other,
+                as_shape: self.play_as(me, other),
+                as_outcome: self.play_as(for_outcome, other),
+                best: self.play_as(best, other),
+            };
+            report.as_shape.add(round.as_shape);
+            report.as_outcome.add(round.as_outcome);
+            report.best.add(round.best);
+            report.rounds.push(round);
         }
+        Ok(report)
     }
 }
 
-pub fn part_1(input: &str) -> u32 {
-    let mut score = 0;
-    for l in input.lines() {
-        let (other, me) = l.split_once(' ').unwrap();
-        let (other, me) = (Rps::from_str(other), Rps::from_str(me));
-        score += me.score() + me.play(&other).score();
-    }
-    score
+pub fn part_1_read(input: &mut dyn BufRead) -> Result<u32> {
+    Game::rock_paper_scissors().score_read(input, Column::Shape)
+}
+pub fn part_2_read(input: &mut dyn BufRead) -> Result<u32> {
+    Game::rock_paper_scissors().score_read(input, Column::Outcome)
+}
+
+pub fn part_1(input: &str) -> Result<u32> {
+    part_1_read(&mut input.as_bytes())
+}
+pub fn part_2(input: &str) -> Result<u32> {
+    part_2_read(&mut input.as_bytes())
 }
-pub fn part_2(input: &str) -> u32 {
-    let mut score = 0;
-    for l in input.lines() {
-        let (other, outcome) = l.split_once(' ').unwrap();
-        let (other, outcome) = (Rps::from_str(other), Outcome::from_str(outcome));
-        let me = Rps::from_outcome(&other, &outcome);
-        score += me.score() + outcome.score();
+
+pub struct Day02;
+impl Solution for Day02 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+    fn part_1(input: &str) -> Result<u32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u32> {
+        part_2(input)
+    }
+    fn part_1_read(input: &mut dyn BufRead) -> Result<u32> {
+        part_1_read(input)
+    }
+    fn part_2_read(input: &mut dyn BufRead) -> Result<u32> {
+        part_2_read(input)
     }
-    score
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day02::*;
-    use std::fs;
+    use crate::manifest::{check, check_read};
     #[test]
     fn day02_part1() {
-        let input = fs::read_to_string("input/day02").unwrap();
-        assert_eq!(part_1(&input), 12276);
+        check(DAY, 1, "day02");
     }
     #[test]
     fn day02_part2() {
-        let input = fs::read_to_string("input/day02").unwrap();
-        assert_eq!(part_2(&input), 9975);
+        check(DAY, 2, "day02");
+    }
+    #[test]
+    fn day02_read() {
+        check_read(DAY, 1, "day02");
+        check_read(DAY, 2, "day02");
+    }
+    #[test]
+    fn cyclic_games() {
+        let rps = Game::rock_paper_scissors();
+        let guide = "A Y\nB X\nC Z\n";
+        assert_eq!(part_1(guide).unwrap(), 15);
+        assert_eq!(part_2(guide).unwrap(), 12);
+        let err = rps.score_read(&mut "A W\n".as_bytes(), Column::Shape);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "day 02, line 1, column 3: expected X, Y or Z, found \"W\""
+        );
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let n = game.shapes().len();
+        for me in 0..n {
+            let wins = (0..n).filter(|&o| game.play(me, o) == Outcome::Win).count();
+            assert_eq!(wins, 2);
+            for other in 0..n {
+                assert_eq!(
+                    game.play(me, other) == Outcome::Win,
+                    game.play(other, me) == Outcome::Loss
+                );
+            }
+        }
+        // Scissors (E) loses to rock and Spock, Spock scores more.
+        assert_eq!(game.respond(4, Outcome::Win), 1);
+        assert_eq!(
+            game.score_read(&mut "E Z\nA X\n".as_bytes(), Column::Outcome)
+                .unwrap(),
+            15
+        );
+        let shape = Shape {
+            opponent: 'A',
+            me: 'X',
+            score: 1,
+        };
+        assert!(Game::new(vec![shape; 2], [('L', 0), ('T', 3), ('W', 6)]).is_err());
+        assert!(Game::new(vec![shape; 3], [('L', 0), ('T', 3), ('W', 6)]).is_err());
+    }
+    #[test]
+    fn analyze_guide() {
+        let report = Game::rock_paper_scissors()
+            .analyze("A Y\nB X\nC Z\n")
+            .unwrap();
+        let t = |wins, ties, losses, score| Totals {
+            wins,
+            ties,
+            losses,
+            score,
+        };
+        assert_eq!(report.as_shape, t(1, 1, 1, 15));
+        assert_eq!(report.as_outcome, t(1, 1, 1, 12));
+        // Paper on rock, scissors on paper, rock on scissors.
+        assert_eq!(report.best, t(3, 0, 0, 24));
+        assert_eq!(
+            report.rounds[1].as_outcome,
+            Play {
+                me: 0,
+                outcome: Outcome::Loss,
+                score: 1
+            }
+        );
+        assert_eq!(
+            report
+                .to_string()
+                .lines()
+                .nth(3)
+                .unwrap()
+                .split_whitespace()
+                .last(),
+            Some("24")
+        );
+        let input = std::fs::read_to_string("input/day02").unwrap();
+        let report = Game::rock_paper_scissors().analyze(&input).unwrap();
+        assert_eq!(report.as_shape.score, part_1(&input).unwrap());
+        assert_eq!(report.as_outcome.score, part_2(&input).unwrap());
     }
 }
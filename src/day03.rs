@@ -1,5 +1,13 @@
+use crate::{
+    error::{Error, Result},
+    parse::{self, Line},
+    solution::Solution,
+};
 use itertools::Itertools;
-use std::collections::HashSet;
+use std::fmt;
+
+const DAY: u8 = 3;
+
 fn prio(b: u8) -> u32 {
     if b.is_ascii_uppercase() {
         (b - b'A') as u32 + 27
@@ -8,53 +16,196 @@ fn prio(b: u8) -> u32 {
     }
 }
 
-pub fn part_1(input: &str) -> u32 {
-    input
-        .lines()
+/// A set of items a-z and A-Z, bit `prio - 1` for each.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub const ALL: Self = Self((1 << 52) - 1);
+    fn bit(item: u8) -> Option<u64> {
+        item.is_ascii_alphabetic().then(|| 1 << (prio(item) - 1))
+    }
+    /// False if `item` isn't a-z or A-Z, and isn't added.
+    pub fn insert(&mut self, item: u8) -> bool {
+        let Some(bit) = Self::bit(item) else {
+            return false;
+        };
+        self.0 |= bit;
+        true
+    }
+    /// `None` if some item isn't a-z or A-Z.
+    pub fn from_items(items: &[u8]) -> Option<Self> {
+        let mut set = Self::default();
+        items.iter().all(|&item| set.insert(item)).then_some(set)
+    }
+    pub fn contains(&self, item: u8) -> bool {
+        Self::bit(item).is_some_and(|bit| self.0 & bit != 0)
+    }
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+    /// The items in priority order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> {
+        let bits = self.0;
+        (b'a'..=b'z')
+            .chain(b'A'..=b'Z')
+            .enumerate()
+            .filter(move |(i, _)| bits & 1 << i != 0)
+            .map(|(_, item)| item)
+    }
+    /// The sum of the items' priorities.
+    pub fn priority(&self) -> u32 {
+        self.iter().map(prio).sum()
+    }
+}
+
+impl fmt::Display for ItemSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter()
+            .try_for_each(|item| write!(f, "{}", item as char))
+    }
+}
+
+/// The line number and items, checked to be a-z or A-Z.
+fn rucksack<'a>(mut l: Line<'a>) -> Result<(usize, &'a [u8])> {
+    let items = l.rest().as_bytes();
+    while !l.is_done() {
+        l.char_map("an item a-z or A-Z", |c| {
+            c.is_ascii_alphabetic().then_some(())
+        })?;
+    }
+    Ok((l.number(), items))
+}
+
+/// The priority of the one item in all of `sets`. `place` says where they're from,
+/// for the error when there isn't exactly one.
+fn common(sets: impl IntoIterator<Item = ItemSet>, place: impl FnOnce() -> String) -> Result<u32> {
+    let common = sets.into_iter().fold(ItemSet::ALL, ItemSet::intersection);
+    match common.len() {
+        1 => Ok(common.priority()),
+        0 => Err(Error::no_answer(DAY, format!("no item {}", place()))),
+        _ => Err(Error::no_answer(DAY, format!("items {common} {}", place()))),
+    }
+}
+
+pub fn part_1(input: &str) -> Result<u32> {
+    parse::lines(DAY, input)
         .map(|l| {
-            let bytes = l.as_bytes();
+            let (line, bytes) = rucksack(l)?;
             let (first, second) = bytes.split_at(bytes.len() / 2);
-            let set = HashSet::<u8>::from_iter(first.iter().cloned());
-            let dup = second.iter().find(|b| set.contains(b)).unwrap();
-            prio(*dup)
+            let compartments =
+                [first, second].map(|c| ItemSet::from_items(c).expect("items are checked"));
+            common(compartments, || {
+                format!("in both compartments on line {line}")
+            })
         })
         .sum()
 }
-pub fn part_2(input: &str) -> u32 {
+
+/// The sum of the badges of groups of `group_size` rucksacks, the one item every
+/// rucksack in the group has.
+pub fn badges(input: &str, group_size: usize) -> Result<u32> {
+    if group_size == 0 {
+        return Err(Error::no_answer(DAY, "groups need at least one rucksack"));
+    }
     let mut sum = 0;
-    for mut group in input.lines().chunks(3).into_iter() {
-        let set_a = HashSet::<u8>::from_iter(group.next().unwrap().bytes());
-        let set_b = HashSet::<u8>::from_iter(group.next().unwrap().bytes());
-        let set_c = HashSet::<u8>::from_iter(group.next().unwrap().bytes());
-        let intersect_a_b: HashSet<u8> = set_a.intersection(&set_b).cloned().collect();
-        let mut intersect = intersect_a_b.intersection(&set_c);
-        sum += prio(*intersect.next().unwrap());
+    for group in parse::lines(DAY, input).chunks(group_size).into_iter() {
+        let group: Vec<_> = group.map(rucksack).collect::<Result<_>>()?;
+        if group.len() < group_size {
+            let expected = format!("a group of {group_size} rucksacks");
+            return Err(parse::missing(DAY, input, expected));
+        }
+        let line = group[0].0;
+        let sets = group
+            .iter()
+            .map(|(_, items)| ItemSet::from_items(items).expect("items are checked"));
+        sum += common(sets, || {
+            format!("common to the group starting on line {line}")
+        })?;
+    }
+    Ok(sum)
+}
+
+pub fn part_2(input: &str) -> Result<u32> {
+    badges(input, 3)
+}
+
+pub struct Day03;
+impl Solution for Day03 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+    fn part_1(input: &str) -> Result<u32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u32> {
+        part_2(input)
     }
-    sum
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day03::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example_day03_part1() {
-        let input = fs::read_to_string("input/example03").unwrap();
-        assert_eq!(part_1(&input), 157);
+        check(DAY, 1, "example03");
     }
     #[test]
     fn day03_part1() {
-        let input = fs::read_to_string("input/day03").unwrap();
-        assert_eq!(part_1(&input), 7863);
+        check(DAY, 1, "day03");
     }
     #[test]
     fn example03_part2() {
-        let input = fs::read_to_string("input/example03").unwrap();
-        assert_eq!(part_2(&input), 70);
+        check(DAY, 2, "example03");
     }
     #[test]
     fn day03_part2() {
-        let input = fs::read_to_string("input/day03").unwrap();
-        assert_eq!(part_2(&input), 2488);
+        check(DAY, 2, "day03");
+    }
+    #[test]
+    fn item_sets() {
+        let a = ItemSet::from_items(b"abcZ").unwrap();
+        let b = ItemSet::from_items(b"cZz").unwrap();
+        assert_eq!(a.intersection(b).to_string(), "cZ");
+        assert_eq!(a.union(b).len(), 5);
+        assert!(a.contains(b'Z') && !a.contains(b'z') && !a.contains(b'1'));
+        assert_eq!(a.intersection(b).priority(), 3 + 52);
+        assert!(ItemSet::default().is_empty());
+        let mut set = a;
+        assert!(!set.insert(b'1') && !set.insert(b'@'));
+        assert_eq!(set, a);
+        assert_eq!(ItemSet::from_items(b"ab1"), None);
+        assert_eq!(ItemSet::ALL.len(), 52);
+    }
+    #[test]
+    fn group_sizes() {
+        let input = std::fs::read_to_string("input/example03").unwrap();
+        assert_eq!(badges(&input, 3), part_2(&input));
+        assert!(badges(&input, 0).is_err());
+        let err = badges("ab\nbc\nab\n", 2).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "day 03, line 4, column 1: expected a group of 2 rucksacks, found nothing"
+        );
+        assert_eq!(badges("abc\nxbd\n", 2).unwrap(), 2);
+        let err = badges("abc\ncbd\n", 1).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "day 03: no answer, items abc common to the group starting on line 1"
+        );
+        let err = badges("ab\ncd\n", 2).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "day 03: no answer, no item common to the group starting on line 1"
+        );
     }
 }
@@ -1,62 +1,80 @@
+use crate::{
+    error::Result,
+    parse::{self, Line},
+    solution::Solution,
+};
 use std::ops::RangeInclusive;
-fn parse_ranges(line: &str) -> (RangeInclusive<u32>, RangeInclusive<u32>) {
-    let parse_range = |s: &str| {
-        s.split_once('-')
-            .map(|(s, e)| s.parse().unwrap()..=e.parse().unwrap())
-            .unwrap()
+
+const DAY: u8 = 4;
+
+fn parse_ranges(mut line: Line) -> Result<(RangeInclusive<u32>, RangeInclusive<u32>)> {
+    let parse_range = |line: &mut Line| -> Result<RangeInclusive<u32>> {
+        let start = line.int()?;
+        line.tag("-")?;
+        Ok(start..=line.int()?)
     };
-    line.split_once(',')
-        .map(|(a, b)| (parse_range(a), parse_range(b)))
-        .unwrap()
+    let a = parse_range(&mut line)?;
+    line.tag(",")?;
+    let b = parse_range(&mut line)?;
+    line.end()?;
+    Ok((a, b))
 }
 
-pub fn part_1(input: &str) -> u32 {
-    input
-        .lines()
+pub fn part_1(input: &str) -> Result<u32> {
+    parse::lines(DAY, input)
         .map(parse_ranges)
-        .map(|(range_a, range_b)| {
+        .map(|ranges| {
+            let (range_a, range_b) = ranges?;
             let contained = (range_a.contains(range_b.start()) && range_a.contains(range_b.end()))
                 || (range_b.contains(range_a.start()) && range_b.contains(range_a.end()));
-            u32::from(contained)
+            Ok(u32::from(contained))
         })
         .sum()
 }
 
-pub fn part_2(input: &str) -> u32 {
-    input
-        .lines()
+pub fn part_2(input: &str) -> Result<u32> {
+    parse::lines(DAY, input)
         .map(parse_ranges)
-        .map(|(range_a, range_b)| {
+        .map(|ranges| {
+            let (range_a, range_b) = ranges?;
             let overlapping = range_a.contains(range_b.start())
                 || range_a.contains(range_b.end())
                 || range_b.contains(range_a.start());
-            u32::from(overlapping)
+            Ok(u32::from(overlapping))
         })
         .sum()
 }
 
+pub struct Day04;
+impl Solution for Day04 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+    fn part_1(input: &str) -> Result<u32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u32> {
+        part_2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::day04::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example04_day_part1() {
-        let input = fs::read_to_string("input/example04").unwrap();
-        assert_eq!(part_1(&input), 2);
+        check(DAY, 1, "example04");
     }
     #[test]
     fn day04_part1() {
-        let input = fs::read_to_string("input/day04").unwrap();
-        assert_eq!(part_1(&input), 534);
+        check(DAY, 1, "day04");
     }
     #[test]
     fn example04_part2() {
-        let input = fs::read_to_string("input/example04").unwrap();
-        assert_eq!(part_2(&input), 4);
+        check(DAY, 2, "example04");
     }
     #[test]
     fn day04_part2() {
-        let input = fs::read_to_string("input/day04").unwrap();
-        assert_eq!(part_2(&input), 841);
+        check(DAY, 2, "day04");
     }
 }
@@ -1,83 +1,137 @@
+use crate::{
+    error::{Error, Result},
+    parse::{self, Line},
+    solution::Solution,
+};
+
+const DAY: u8 = 5;
+
 struct Move {
     count: usize,
     from: usize,
     to: usize,
+    line: usize,
 }
 impl Move {
-    fn parse(s: &str) -> Self {
-        let (count, rest) = s.trim_start_matches("move ").split_once(" from ").unwrap();
-        let (from, to) = rest.split_once(" to ").unwrap();
-        Self {
-            count: count.parse().unwrap(),
-            from: from.parse::<usize>().unwrap() - 1,
-            to: to.parse::<usize>().unwrap() - 1,
-        }
+    fn parse(mut l: Line, stack_count: usize) -> Result<Self> {
+        let stack = |l: &mut Line| -> Result<usize> {
+            let n = l.int::<usize>()?;
+            if !(1..=stack_count).contains(&n) {
+                return Err(l.error(format!("a stack number 1-{stack_count}")));
+            }
+            Ok(n - 1)
+        };
+        l.tag("move ")?;
+        let count = l.int()?;
+        l.tag(" from ")?;
+        let from = stack(&mut l)?;
+        l.tag(" to ")?;
+        let to = stack(&mut l)?;
+        l.end()?;
+        Ok(Self {
+            count,
+            from,
+            to,
+            line: l.number(),
+        })
     }
 }
 
 /// Reads "_modified" input files
-fn parse_stacks_and_moves(s: &str) -> (Vec<Vec<u8>>, Vec<Move>) {
-    let (stacks, moves) = s.split_once("\n\n").unwrap();
-    let stacks = stacks
+fn parse_stacks_and_moves(s: &str) -> Result<(Vec<Vec<u8>>, Vec<Move>)> {
+    let mut blocks = parse::blocks(s);
+    let (_, stacks) = blocks.next().unwrap_or_default();
+    let stacks: Vec<Vec<u8>> = stacks
         .lines()
         .map(|l| l.split_whitespace().map(|s| s.as_bytes()[0]).collect())
         .collect();
-    let moves = moves.lines().map(Move::parse).collect();
-    (stacks, moves)
+    let (first, moves) = blocks
+        .next()
+        .ok_or_else(|| parse::missing(DAY, s, "a blank line followed by moves"))?;
+    let moves = parse::lines_from(DAY, first, moves)
+        .map(|l| Move::parse(l, stacks.len()))
+        .collect::<Result<_>>()?;
+    Ok((stacks, moves))
+}
+
+fn too_few_crates(m: &Move) -> Error {
+    Error::no_answer(DAY, format!("too few crates to move on line {}", m.line))
+}
+
+fn top_crates(stacks: &[Vec<u8>]) -> Result<String> {
+    let ans = stacks
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            s.last()
+                .cloned()
+                .ok_or_else(|| Error::no_answer(DAY, format!("stack {} ends up empty", i + 1)))
+        })
+        .collect::<Result<Vec<u8>>>()?;
+    Ok(String::from_utf8_lossy(&ans).into_owned())
 }
 
-pub fn part_1(input: &str) -> String {
-    let (mut stacks, moves) = parse_stacks_and_moves(input);
+pub fn part_1(input: &str) -> Result<String> {
+    let (mut stacks, moves) = parse_stacks_and_moves(input)?;
     for m in moves {
         for _ in 0..m.count {
-            let pop = stacks[m.from].pop().unwrap();
+            let pop = stacks[m.from].pop().ok_or_else(|| too_few_crates(&m))?;
             stacks[m.to].push(pop);
         }
     }
-    let ans: Vec<u8> = stacks.iter().map(|s| s.last().unwrap()).cloned().collect();
-    String::from_utf8(ans).unwrap()
+    top_crates(&stacks)
 }
 
-use std::mem;
-pub fn part_2(input: &str) -> String {
-    let (mut stacks, moves) = parse_stacks_and_moves(input);
+pub fn part_2(input: &str) -> Result<String> {
+    let (mut stacks, moves) = parse_stacks_and_moves(input)?;
     for m in moves {
-        // Yikes Rust, two mut ref is hard. v3 with mem:swap
-        let mut to = Vec::new();
-        let mut from = Vec::new();
-        mem::swap(stacks.get_mut(m.to).unwrap(), &mut to);
-        mem::swap(stacks.get_mut(m.from).unwrap(), &mut from);
-        to.extend_from_slice(&from[from.len() - m.count..]);
-        from.truncate(from.len() - m.count);
-        mem::swap(stacks.get_mut(m.to).unwrap(), &mut to);
-        mem::swap(stacks.get_mut(m.from).unwrap(), &mut from);
+        if stacks[m.from].len() < m.count {
+            return Err(too_few_crates(&m));
+        }
+        // Taken out first, so moving onto the same stack puts them back.
+        let from = &mut stacks[m.from];
+        let moved: Vec<u8> = from.drain(from.len() - m.count..).collect();
+        stacks[m.to].extend(moved);
+    }
+    top_crates(&stacks)
+}
+
+pub struct Day05;
+impl Solution for Day05 {
+    type Answer1 = String;
+    type Answer2 = String;
+    fn part_1(input: &str) -> Result<String> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<String> {
+        part_2(input)
     }
-    let ans: Vec<u8> = stacks.iter().map(|s| s.last().unwrap()).cloned().collect();
-    String::from_utf8(ans).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day05::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example05_day_part1() {
-        let input = fs::read_to_string("input/example05_modified").unwrap();
-        assert_eq!(part_1(&input), "CMZ");
+        check(DAY, 1, "example05_modified");
     }
     #[test]
     fn day05_part1() {
-        let input = fs::read_to_string("input/day05_modified").unwrap();
-        assert_eq!(part_1(&input), "VWLCWGSDQ");
+        check(DAY, 1, "day05_modified");
     }
     #[test]
     fn example05_day_part2() {
-        let input = fs::read_to_string("input/example05_modified").unwrap();
-        assert_eq!(part_2(&input), "MCD");
+        check(DAY, 2, "example05_modified");
     }
     #[test]
     fn day05_part2() {
-        let input = fs::read_to_string("input/day05_modified").unwrap();
-        assert_eq!(part_2(&input), "TCGLQSLPW");
+        check(DAY, 2, "day05_modified");
+    }
+    #[test]
+    fn same_stack_move() {
+        let input = "Z N\nM C D\n\nmove 2 from 2 to 2\nmove 1 from 1 to 1\n";
+        assert_eq!(part_1(input).unwrap(), "ND");
+        assert_eq!(part_2(input).unwrap(), "ND");
     }
 }
@@ -1,54 +1,96 @@
-use std::collections::HashSet;
-
-pub fn part_1(input: &str) -> usize {
-    let unique = |a, b, c, d| a != b && a != c && a != d && b != c && b != d && c != d;
-    input
-        .as_bytes()
-        .windows(4)
-        .enumerate()
-        .find(|(_, win)| unique(win[0], win[1], win[2], win[3]))
-        .unwrap()
-        .0
-        + 4
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, Read},
+};
+
+const DAY: u8 = 6;
+
+/// Number of bytes read up to and including the first `n` distinct ones in a row.
+/// Reads one byte at a time, keeping only the last `n`.
+fn find_marker(input: &mut dyn BufRead, n: usize) -> Result<Option<usize>> {
+    let mut window = VecDeque::with_capacity(n);
+    for (i, byte) in Read::bytes(input).enumerate() {
+        if window.len() == n {
+            window.pop_front();
+        }
+        window.push_back(byte?);
+        let unique = || {
+            let mut rest = window.iter();
+            while let Some(b) = rest.next() {
+                if rest.clone().any(|other| other == b) {
+                    return false;
+                }
+            }
+            true
+        };
+        if window.len() == n && unique() {
+            return Ok(Some(i + 1));
+        }
+    }
+    Ok(None)
+}
+
+pub fn part_1_read(input: &mut dyn BufRead) -> Result<usize> {
+    find_marker(input, 4)?.ok_or_else(|| Error::no_answer(DAY, "no start-of-packet marker"))
+}
+
+pub fn part_2_read(input: &mut dyn BufRead) -> Result<usize> {
+    find_marker(input, 14)?.ok_or_else(|| Error::no_answer(DAY, "no start-of-message marker"))
+}
+
+pub fn part_1(input: &str) -> Result<usize> {
+    part_1_read(&mut input.as_bytes())
 }
 
-pub fn part_2(input: &str) -> usize {
-    let unique = |win: &[u8]| {
-        let mut set = HashSet::with_capacity(14);
-        win.iter().all(|byte| set.insert(*byte))
-    };
-    input
-        .as_bytes()
-        .windows(14)
-        .enumerate()
-        .find(|(_, win)| unique(win))
-        .unwrap()
-        .0
-        + 14
+pub fn part_2(input: &str) -> Result<usize> {
+    part_2_read(&mut input.as_bytes())
+}
+
+pub struct Day06;
+impl Solution for Day06 {
+    type Answer1 = usize;
+    type Answer2 = usize;
+    fn part_1(input: &str) -> Result<usize> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<usize> {
+        part_2(input)
+    }
+    fn part_1_read(input: &mut dyn BufRead) -> Result<usize> {
+        part_1_read(input)
+    }
+    fn part_2_read(input: &mut dyn BufRead) -> Result<usize> {
+        part_2_read(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day06::*;
-    use std::fs;
+    use crate::manifest::{check, check_read};
     #[test]
     fn example06_day_part1() {
-        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
-        assert_eq!(part_1(input), 7);
+        check(DAY, 1, "example06");
     }
     #[test]
     fn day06_part1() {
-        let input = fs::read_to_string("input/day06").unwrap();
-        assert_eq!(part_1(&input), 1876);
+        check(DAY, 1, "day06");
     }
     #[test]
     fn example06_part2() {
-        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
-        assert_eq!(part_2(input), 19);
+        check(DAY, 2, "example06");
     }
     #[test]
     fn day06_part2() {
-        let input = fs::read_to_string("input/day06").unwrap();
-        assert_eq!(part_2(&input), 2202);
+        check(DAY, 2, "day06");
+    }
+    #[test]
+    fn day06_read() {
+        check_read(DAY, 1, "day06");
+        check_read(DAY, 2, "day06");
     }
 }
@@ -1,100 +1,124 @@
+use crate::{
+    error::{Error, Result},
+    parse,
+    solution::Solution,
+};
 use std::collections::HashMap;
 
+const DAY: u8 = 7;
+
 struct Dir {
     files: u64,
     subdirs: Vec<String>,
 }
-fn get_dir_map(input: &str) -> HashMap<String, Dir> {
-    let mut lines = input.lines().peekable();
+fn get_dir_map(input: &str) -> Result<HashMap<String, Dir>> {
+    let mut lines = parse::lines(DAY, input).peekable();
     let mut path = Vec::new();
     let mut result = HashMap::new();
-    while let Some(next) = lines.next() {
-        if next == "$ cd .." {
+    while let Some(mut next) = lines.next() {
+        if next.try_tag("$ cd ..") {
+            next.end()?;
             path.pop();
             continue;
         }
-        if let Some(subdir) = next.strip_prefix("$ cd ") {
-            path.push(subdir);
+        if next.try_tag("$ cd ") {
+            path.push(next.rest());
             continue;
         } else {
-            assert!(next == "$ ls");
+            next.tag("$ ls")?;
+            next.end()?;
         }
         // read ls printout
         let mut files = 0;
         let mut subdirs = Vec::new();
-        while lines.peek().filter(|l| !l.starts_with('$')).is_some() {
-            let content = lines.next().unwrap();
-            if let Some(subdir) = content.strip_prefix("dir ") {
-                subdirs.push(subdir.to_string());
+        while lines
+            .peek()
+            .filter(|l| !l.rest().starts_with('$'))
+            .is_some()
+        {
+            let mut content = lines.next().unwrap();
+            if content.try_tag("dir ") {
+                subdirs.push(content.rest().to_string());
             } else {
                 // only care about file size at the moment
-                files += content.split_once(' ').unwrap().0.parse::<u64>().unwrap();
+                files += content.int::<u64>()?;
+                content.tag(" ")?;
             }
         }
         result.insert(path.join("/"), Dir { files, subdirs });
     }
-    result
+    Ok(result)
 }
 
 fn get_dir_size(
     path: &str,
     dir_map: &HashMap<String, Dir>,
     size_map: &mut HashMap<String, u64>,
-) -> u64 {
-    let dir = &dir_map[path];
+) -> Result<u64> {
+    let dir = dir_map
+        .get(path)
+        .ok_or_else(|| Error::no_answer(DAY, format!("{path} is never listed")))?;
     let mut size: u64 = dir.files;
     for subdir in &dir.subdirs {
         let subpath = path.to_string() + "/" + subdir;
-        size += get_dir_size(&subpath, dir_map, size_map);
+        size += get_dir_size(&subpath, dir_map, size_map)?;
     }
     size_map.insert(path.to_string(), size);
-    size
+    Ok(size)
 }
 
-pub fn part_1(input: &str) -> u64 {
-    let dir_map = get_dir_map(input);
+pub fn part_1(input: &str) -> Result<u64> {
+    let dir_map = get_dir_map(input)?;
     let mut size_map = HashMap::new();
-    get_dir_size("/", &dir_map, &mut size_map);
-    size_map
+    get_dir_size("/", &dir_map, &mut size_map)?;
+    Ok(size_map
         .iter()
         .filter_map(|(_k, &v)| if v < 100_000 { Some(v) } else { None })
-        .sum()
+        .sum())
 }
 
-pub fn part_2(input: &str) -> u64 {
-    let dir_map = get_dir_map(input);
+pub fn part_2(input: &str) -> Result<u64> {
+    let dir_map = get_dir_map(input)?;
     let mut size_map = HashMap::new();
-    get_dir_size("/", &dir_map, &mut size_map);
-    let to_cut = 30_000_000 - (70_000_000 - size_map["/"]);
+    let used = get_dir_size("/", &dir_map, &mut size_map)?;
+    let to_cut = (used + 30_000_000).saturating_sub(70_000_000);
     size_map
-        .iter()
-        .filter_map(|(_k, size)| if *size >= to_cut { Some(*size) } else { None })
+        .values()
+        .filter_map(|size| if *size >= to_cut { Some(*size) } else { None })
         .min()
-        .unwrap()
+        .ok_or_else(|| Error::no_answer(DAY, "no directory is big enough"))
+}
+
+pub struct Day07;
+impl Solution for Day07 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+    fn part_1(input: &str) -> Result<u64> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u64> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day07::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example07_day_part1() {
-        let input = fs::read_to_string("input/example07").unwrap();
-        assert_eq!(part_1(&input), 95437);
+        check(DAY, 1, "example07");
     }
     #[test]
     fn day07_part1() {
-        let input = fs::read_to_string("input/day07").unwrap();
-        assert_eq!(part_1(&input), 1443806);
+        check(DAY, 1, "day07");
     }
     #[test]
     fn example07_part2() {
-        let input = fs::read_to_string("input/example07").unwrap();
-        assert_eq!(part_2(&input), 24933642);
+        check(DAY, 2, "example07");
     }
     #[test]
     fn day07_part2() {
-        let input = fs::read_to_string("input/day07").unwrap();
-        assert_eq!(part_2(&input), 942298);
+        check(DAY, 2, "day07");
     }
 }
@@ -1,17 +1,42 @@
+use crate::{error::Result, grid::Grid, solution::Solution, strategy::Strategy};
 use std::{cmp, collections::HashSet};
 
-fn read_map(s: &str) -> Vec<Vec<i8>> {
-    s.lines()
-        .map(|l| l.chars().map(|c| c.to_digit(10).unwrap() as i8).collect())
-        .collect()
+const DAY: u8 = 8;
+
+fn read_map(s: &str) -> Result<Grid<i8>> {
+    Grid::parse(DAY, s, "a digit", |c| c.to_digit(10).map(|d| d as i8))
+}
+
+pub fn part_1(input: &str) -> Result<usize> {
+    let map = read_map(input)?;
+    let mut visible = Grid::new(map.width(), map.height(), false);
+    let mut sweep = |line: &mut dyn Iterator<Item = crate::grid::Pos>| {
+        let mut tallest = -1;
+        for pos in line {
+            if map[pos] > tallest {
+                tallest = map[pos];
+                visible[pos] = true;
+            }
+        }
+    };
+    for y in 0..map.height() {
+        sweep(&mut (0..map.width()).map(|x| (x, y)));
+        sweep(&mut (0..map.width()).rev().map(|x| (x, y)));
+    }
+    for x in 0..map.width() {
+        sweep(&mut (0..map.height()).map(|y| (x, y)));
+        sweep(&mut (0..map.height()).rev().map(|y| (x, y)));
+    }
+    Ok(visible.iter().filter(|(_, &v)| v).count())
 }
 
-pub fn part_1(input: &str) -> usize {
-    let map = read_map(input);
+/// The first take on part 1, kept as a reference for `part_1`.
+pub fn part_1_sets(input: &str) -> Result<usize> {
+    let map = read_map(input)?;
     // find hidden from west & east
     let mut hidden_from_east = HashSet::new();
     let mut hidden_from_west = HashSet::new();
-    for (y, row) in map.iter().enumerate() {
+    for (y, row) in map.rows().enumerate() {
         let mut tallest_from_west = -1;
         row.iter().enumerate().for_each(|(x, next)| {
             if next > &tallest_from_west {
@@ -32,19 +57,19 @@ pub fn part_1(input: &str) -> usize {
     // find hidden from north & south
     let mut hidden_from_north = HashSet::new();
     let mut hidden_from_south = HashSet::new();
-    for x in 0..map[0].len() {
+    for x in 0..map.width() {
         let mut tallest_from_north = -1;
-        map.iter().enumerate().for_each(|(y, column)| {
-            if column[x] > tallest_from_north {
-                tallest_from_north = column[x];
+        map.column(x).enumerate().for_each(|(y, next)| {
+            if next > &tallest_from_north {
+                tallest_from_north = *next;
             } else {
                 hidden_from_north.insert((x, y));
             }
         });
         let mut tallest_from_south = -1;
-        map.iter().enumerate().rev().for_each(|(y, column)| {
-            if column[x] > tallest_from_south {
-                tallest_from_south = column[x];
+        map.column(x).enumerate().rev().for_each(|(y, next)| {
+            if next > &tallest_from_south {
+                tallest_from_south = *next;
             } else {
                 hidden_from_south.insert((x, y));
             }
@@ -53,65 +78,88 @@ pub fn part_1(input: &str) -> usize {
     let hidden_ew: HashSet<_> = hidden_from_east.intersection(&hidden_from_west).collect();
     let hidden_ns: HashSet<_> = hidden_from_north.intersection(&hidden_from_south).collect();
     let hidden = hidden_ew.intersection(&hidden_ns).count();
-    map.len() * map[0].len() - hidden
+    Ok(map.width() * map.height() - hidden)
 }
 
-pub fn part_2(input: &str) -> u32 {
-    let map = read_map(input);
+pub fn part_2(input: &str) -> Result<u32> {
+    let map = read_map(input)?;
     let mut best_score = 0;
-    for y in 0..map.len() {
-        for x in 0..map[0].len() {
-            let house = map[y][x];
-            let score_n = map[0..y]
-                .iter()
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            let house = map[(x, y)];
+            let score_n = map
+                .column(x)
+                .take(y)
                 .rev()
                 .enumerate()
-                .find_map(|(i, col)| (house <= col[x]).then_some(i + 1))
+                .find_map(|(i, &tree)| (house <= tree).then_some(i + 1))
                 .unwrap_or(y);
-            let score_s = map[y + 1..]
-                .iter()
+            let score_s = map
+                .column(x)
+                .skip(y + 1)
                 .enumerate()
-                .find_map(|(i, col)| (house <= col[x]).then_some(i + 1))
-                .unwrap_or(map.len() - y - 1);
-            let score_w = map[y][0..x]
+                .find_map(|(i, &tree)| (house <= tree).then_some(i + 1))
+                .unwrap_or(map.height() - y - 1);
+            let score_w = map.row(y)[0..x]
                 .iter()
                 .rev()
                 .enumerate()
                 .find_map(|(i, &tree)| (house <= tree).then_some(i + 1))
                 .unwrap_or(x);
-            let score_e = map[y][x + 1..]
+            let score_e = map.row(y)[x + 1..]
                 .iter()
                 .enumerate()
                 .find_map(|(i, &tree)| (house <= tree).then_some(i + 1))
-                .unwrap_or(map[0].len() - x - 1);
+                .unwrap_or(map.width() - x - 1);
             best_score = cmp::max(score_e * score_n * score_s * score_w, best_score);
         }
     }
-    best_score as u32
+    Ok(best_score as u32)
+}
+
+pub const STRATEGIES: [Strategy; 2] = [
+    Strategy {
+        name: "sweep",
+        part: 1,
+        run: |input| part_1(input).map(Into::into),
+    },
+    Strategy {
+        name: "sets",
+        part: 1,
+        run: |input| part_1_sets(input).map(Into::into),
+    },
+];
+
+pub struct Day08;
+impl Solution for Day08 {
+    type Answer1 = usize;
+    type Answer2 = u32;
+    fn part_1(input: &str) -> Result<usize> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u32> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day08::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example08_day_part1() {
-        let input = fs::read_to_string("input/example08").unwrap();
-        assert_eq!(part_1(&input), 21);
+        check(DAY, 1, "example08");
     }
     #[test]
     fn day08_part1() {
-        let input = fs::read_to_string("input/day08").unwrap();
-        assert_eq!(part_1(&input), 1820);
+        check(DAY, 1, "day08");
     }
     #[test]
     fn example08_part2() {
-        let input = fs::read_to_string("input/example08").unwrap();
-        assert_eq!(part_2(&input), 8);
+        check(DAY, 2, "example08");
     }
     #[test]
     fn day08_part2() {
-        let input = fs::read_to_string("input/day08").unwrap();
-        assert_eq!(part_2(&input), 385112);
+        check(DAY, 2, "day08");
     }
 }
@@ -1,15 +1,14 @@
+use crate::{error::Result, parse, simulation::Simulation, solution::Solution};
 use std::collections::HashSet;
+
+const DAY: u8 = 9;
+
 #[derive(Hash, PartialEq, Eq, Clone, Copy)]
 struct Knot(i32, i32);
 impl Knot {
-    fn move_head(&mut self, dir: &str) {
-        match dir {
-            "U" => self.1 += 1,
-            "D" => self.1 -= 1,
-            "R" => self.0 += 1,
-            "L" => self.0 -= 1,
-            _ => panic!(),
-        }
+    fn move_head(&mut self, (dx, dy): (i32, i32)) {
+        self.0 += dx;
+        self.1 += dy;
     }
     fn move_tail(&mut self, head: &Knot) {
         let dx = head.0 - self.0;
@@ -20,61 +19,133 @@ impl Knot {
         }
     }
 }
-pub fn part_1(input: &str) -> usize {
-    let mut head = Knot(0, 0);
-    let mut tail = Knot(0, 0);
-    let mut visited = HashSet::<Knot>::new();
-    visited.insert(tail);
-    for (dir, n) in input.lines().map(|l| l.split_once(' ').unwrap()) {
-        let n: usize = n.parse().unwrap();
-        for _ in 0..n {
-            head.move_head(dir);
-            tail.move_tail(&head);
-            visited.insert(tail);
-        }
+fn parse_moves(input: &str) -> impl Iterator<Item = Result<((i32, i32), usize)>> + '_ {
+    parse::lines(DAY, input).map(|mut l| {
+        let dir = l.char_map("U, D, R or L", |c| match c {
+            'U' => Some((0, 1)),
+            'D' => Some((0, -1)),
+            'R' => Some((1, 0)),
+            'L' => Some((-1, 0)),
+            _ => None,
+        })?;
+        l.tag(" ")?;
+        let n = l.int()?;
+        l.end()?;
+        Ok((dir, n))
+    })
+}
+
+/// A rope being pulled by its head, one square per step.
+pub struct Rope {
+    knots: Vec<Knot>,
+    moves: std::vec::IntoIter<((i32, i32), usize)>,
+    /// Direction the head is going and squares left to go.
+    current: ((i32, i32), usize),
+    visited: HashSet<Knot>,
+    ticks: usize,
+}
+
+impl Rope {
+    pub fn new(input: &str, knots: usize) -> Result<Self> {
+        let moves: Vec<_> = parse_moves(input).collect::<Result<_>>()?;
+        let knots = vec![Knot(0, 0); knots.max(1)];
+        Ok(Self {
+            visited: HashSet::from([knots[knots.len() - 1]]),
+            knots,
+            moves: moves.into_iter(),
+            current: ((0, 0), 0),
+            ticks: 0,
+        })
+    }
+    /// Squares the tail has been on so far.
+    pub fn visited(&self) -> usize {
+        self.visited.len()
     }
-    visited.len()
 }
-pub fn part_2(input: &str) -> usize {
-    let mut knots = [Knot(0, 0); 10];
-    let mut visited = HashSet::<Knot>::new();
-    visited.insert(knots[9]);
-    for (dir, n) in input.lines().map(|l| l.split_once(' ').unwrap()) {
-        let n: usize = n.parse().unwrap();
-        for _ in 0..n {
-            knots[0].move_head(dir);
-            for i in 1..10 {
-                let head = knots[i - 1];
-                knots[i].move_tail(&head);
+
+impl Simulation for Rope {
+    /// Knot positions, head first.
+    type State = Vec<(i32, i32)>;
+    fn step(&mut self) {
+        while self.current.1 == 0 {
+            match self.moves.next() {
+                Some(m) => self.current = m,
+                None => return,
             }
-            visited.insert(knots[9]);
         }
+        self.current.1 -= 1;
+        self.knots[0].move_head(self.current.0);
+        for i in 1..self.knots.len() {
+            let head = self.knots[i - 1];
+            self.knots[i].move_tail(&head);
+        }
+        self.visited.insert(self.knots[self.knots.len() - 1]);
+        self.ticks += 1;
+    }
+    fn is_done(&self) -> bool {
+        self.current.1 == 0 && self.moves.as_slice().iter().all(|&(_, n)| n == 0)
+    }
+    fn snapshot(&self) -> Self::State {
+        self.knots.iter().map(|k| (k.0, k.1)).collect()
+    }
+    fn ticks(&self) -> usize {
+        self.ticks
+    }
+}
+
+pub fn part_1(input: &str) -> Result<usize> {
+    let mut rope = Rope::new(input, 2)?;
+    rope.run(usize::MAX);
+    Ok(rope.visited())
+}
+pub fn part_2(input: &str) -> Result<usize> {
+    let mut rope = Rope::new(input, 10)?;
+    rope.run(usize::MAX);
+    Ok(rope.visited())
+}
+
+pub struct Day09;
+impl Solution for Day09 {
+    type Answer1 = usize;
+    type Answer2 = usize;
+    fn part_1(input: &str) -> Result<usize> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<usize> {
+        part_2(input)
     }
-    visited.len()
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day09::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example09_day_part1() {
-        let input = fs::read_to_string("input/example09").unwrap();
-        assert_eq!(part_1(&input), 13);
+        check(DAY, 1, "example09");
     }
     #[test]
     fn day09_part1() {
-        let input = fs::read_to_string("input/day09").unwrap();
-        assert_eq!(part_1(&input), 6284);
+        check(DAY, 1, "day09");
     }
     #[test]
     fn example09_part2() {
-        let input = fs::read_to_string("input/example09_part2").unwrap();
-        assert_eq!(part_2(&input), 36);
+        check(DAY, 2, "example09_part2");
     }
     #[test]
     fn day09_part2() {
-        let input = fs::read_to_string("input/day09").unwrap();
-        assert_eq!(part_2(&input), 2661);
+        check(DAY, 2, "day09");
+    }
+    #[test]
+    fn rope_steps() {
+        let mut rope = Rope::new("R 4\nU 0\nU 2\n", 3).unwrap();
+        rope.run(4);
+        assert_eq!(rope.snapshot(), [(4, 0), (3, 0), (2, 0)]);
+        rope.step();
+        assert_eq!(rope.snapshot(), [(4, 1), (3, 0), (2, 0)]);
+        rope.step();
+        assert_eq!(rope.snapshot(), [(4, 2), (4, 1), (3, 1)]);
+        assert!(rope.is_done());
+        assert_eq!((rope.ticks(), rope.visited()), (6, 4));
     }
 }
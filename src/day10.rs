@@ -1,38 +1,80 @@
-struct Cpu<'a> {
+use crate::{
+    error::{Error, Result},
+    parse,
+    render::{self, Color, Pixel, Renderer},
+    simulation::Simulation,
+    solution::Solution,
+};
+
+const DAY: u8 = 10;
+
+/// Runs the program one cycle per step.
+pub struct Cpu {
     register: i32,
     wait: usize,
     to_add: i32,
-    instructions: std::str::Lines<'a>,
+    /// `None` is noop, `Some(n)` is addx n.
+    instructions: std::vec::IntoIter<Option<i32>>,
+    cycles: usize,
 }
 
-impl<'a> Cpu<'a> {
-    fn new(i: &'a str) -> Self {
-        Self {
+impl Cpu {
+    pub fn new(i: &str) -> Result<Self> {
+        let instructions: Vec<_> = parse::lines(DAY, i)
+            .map(|mut l| {
+                let instruction = if l.try_tag("noop") {
+                    None
+                } else {
+                    l.tag("addx ")?;
+                    Some(l.int()?)
+                };
+                l.end()?;
+                Ok(instruction)
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
             register: 1,
-            instructions: i.lines(),
+            instructions: instructions.into_iter(),
             wait: 0,
             to_add: 0,
-        }
+            cycles: 0,
+        })
     }
+}
 
+impl Simulation for Cpu {
+    /// The register during the last cycle.
+    type State = i32;
     fn step(&mut self) {
+        self.cycles += 1;
         if self.wait > 0 {
             self.wait -= 1;
             return;
         }
         self.register += self.to_add;
-        match self.instructions.next().unwrap() {
-            "noop" => self.to_add = 0,
-            addx => {
+        // Once the program runs out the cpu idles, as if on noop.
+        match self.instructions.next().flatten() {
+            None => self.to_add = 0,
+            Some(addx) => {
                 self.wait = 1;
-                self.to_add = addx.trim_start_matches("addx ").parse::<i32>().unwrap();
+                self.to_add = addx;
             }
         }
     }
+    /// Done once the program has run out and its last addx has landed.
+    fn is_done(&self) -> bool {
+        self.wait == 0 && self.to_add == 0 && self.instructions.len() == 0
+    }
+    fn snapshot(&self) -> i32 {
+        self.register
+    }
+    fn ticks(&self) -> usize {
+        self.cycles
+    }
 }
 
-pub fn part_1(input: &str) -> i32 {
-    let mut cpu = Cpu::new(input);
+pub fn part_1(input: &str) -> Result<i32> {
+    let mut cpu = Cpu::new(input)?;
     let mut ans = 0;
     for _ in 0..20 {
         cpu.step();
@@ -44,7 +86,7 @@ pub fn part_1(input: &str) -> i32 {
         }
         ans += c * cpu.register;
     }
-    ans
+    Ok(ans)
 }
 
 fn render(mut cpu: Cpu) -> String {
@@ -63,39 +105,166 @@ fn render(mut cpu: Cpu) -> String {
     image
 }
 
-pub fn part_2(input: &str) -> String {
-    let cpu = Cpu::new(input);
-    render(cpu)
+/// The CRT picture, six rows of `#` and `.`.
+pub fn image(input: &str) -> Result<String> {
+    let cpu = Cpu::new(input)?;
+    Ok(render(cpu))
+}
+
+/// The 4x6 capital letters the CRT draws, one column apart.
+pub(crate) const FONT: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// A glyph as bits, row by row from the top left, `#` set.
+fn glyph<'a>(rows: impl IntoIterator<Item = &'a str>) -> u32 {
+    rows.into_iter()
+        .flat_map(str::chars)
+        .fold(0, |bits, c| bits << 1 | u32::from(c == '#'))
+}
+
+/// Reads the letters in a CRT picture. Fails listing the bitmaps of glyphs that
+/// aren't in the font.
+pub fn read(image: &str) -> Result<String> {
+    let rows: Vec<Vec<char>> = image.lines().map(|l| l.chars().collect()).collect();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut letters = String::new();
+    let mut unknown = Vec::new();
+    for (i, x) in (0..width).step_by(5).enumerate() {
+        let glyph_rows: Vec<String> = rows
+            .iter()
+            .map(|r| {
+                (x..x + 4)
+                    .map(|x| r.get(x).copied().unwrap_or('.'))
+                    .collect()
+            })
+            .collect();
+        let bits = glyph(glyph_rows.iter().map(String::as_str));
+        match FONT.iter().find(|(_, g)| glyph(*g) == bits) {
+            Some((c, _)) => letters.push(*c),
+            None => unknown.push(format!("{i}: {bits:#08x} ({})", glyph_rows.join("/"))),
+        }
+    }
+    if !unknown.is_empty() {
+        let reason = format!("unknown glyphs {}", unknown.join(", "));
+        return Err(Error::no_answer(DAY, reason));
+    }
+    Ok(letters)
+}
+
+pub fn part_2(input: &str) -> Result<String> {
+    read(&image(input)?)
+}
+
+/// The CRT being drawn, one frame per cycle, with the sprite shown on the current row.
+pub fn visualize(input: &str, _part: u8, renderer: &mut dyn Renderer) -> Result<()> {
+    let mut cpu = Cpu::new(input)?;
+    let mut screen = render::canvas(40, 6);
+    for row in 0..6 {
+        for col in 0..40 {
+            cpu.step();
+            if (cpu.register - col as i32).abs() < 2 {
+                screen[(col, row)] = Pixel::new('#', Color::GREEN);
+            }
+            let mut frame = screen.clone();
+            for x in cpu.register - 1..=cpu.register + 1 {
+                let Ok(x) = usize::try_from(x) else { continue };
+                if let Some(p) = frame.get_mut((x, row)).filter(|p| **p == Pixel::EMPTY) {
+                    *p = Pixel::new('=', Color::BLUE);
+                }
+            }
+            frame[(col, row)].color = Color::YELLOW;
+            renderer.frame(&frame)?;
+        }
+    }
+    Ok(())
+}
+
+pub struct Day10;
+impl Solution for Day10 {
+    type Answer1 = i32;
+    type Answer2 = String;
+    fn part_1(input: &str) -> Result<i32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<String> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day10::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example10_day_part1() {
-        let input = fs::read_to_string("input/example10").unwrap();
-        assert_eq!(part_1(&input), 13140);
+        check(DAY, 1, "example10");
     }
     #[test]
     fn day10_part1() {
-        let input = fs::read_to_string("input/day10").unwrap();
-        assert_eq!(part_1(&input), 11960);
+        check(DAY, 1, "day10");
     }
     #[test]
     fn example10_part2() {
-        let input = fs::read_to_string("input/example10").unwrap();
-        assert_eq!(
-            part_2(&input),
-            fs::read_to_string("input/example10_image").unwrap()
+        let input = std::fs::read_to_string("input/example10").unwrap();
+        let expected = std::fs::read_to_string("input/example10_image").unwrap();
+        assert_eq!(image(&input).unwrap(), expected);
+        let err = part_2(&input).unwrap_err().to_string();
+        assert!(
+            err.starts_with("day 10: no answer, unknown glyphs 0: 0xceffff (##../###./####/####/####/####), 1: "),
+            "{err}"
         );
     }
     #[test]
     fn day10_part2() {
-        let input = fs::read_to_string("input/day10").unwrap();
-        assert_eq!(
-            part_2(&input),
-            fs::read_to_string("input/day10_image").unwrap()
-        );
+        check(DAY, 2, "day10");
+        let input = std::fs::read_to_string("input/day10").unwrap();
+        let expected = std::fs::read_to_string("input/day10_image").unwrap();
+        assert_eq!(image(&input).unwrap(), expected);
+    }
+    #[test]
+    fn read_font() {
+        let image = (0..6)
+            .map(|row| FONT.map(|(_, g)| g[row]).join("."))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let letters: String = FONT.iter().map(|(c, _)| c).collect();
+        assert_eq!(read(&image).unwrap(), letters);
+    }
+    #[test]
+    fn visualize_example() {
+        let input = std::fs::read_to_string("input/example10").unwrap();
+        let mut frames = crate::render::Frames(Vec::new());
+        visualize(&input, 2, &mut frames).unwrap();
+        assert_eq!(frames.0.len(), 240);
+        let last = frames.0[239].render(|p| if p.ch == '=' { '.' } else { p.ch });
+        assert_eq!(last, image(&input).unwrap());
+    }
+    #[test]
+    fn cpu_cycles() {
+        let mut cpu = Cpu::new("noop\naddx 3\naddx -5\n").unwrap();
+        let mut registers = Vec::new();
+        while !cpu.is_done() {
+            cpu.step();
+            registers.push(cpu.snapshot());
+        }
+        assert_eq!(registers, [1, 1, 1, 4, 4, -1]);
+        assert_eq!(cpu.ticks(), 6);
     }
 }
@@ -1,13 +1,105 @@
+use crate::{
+    error::Result,
+    parse::{self, Line},
+    solution::Solution,
+};
 use std::collections::VecDeque;
 
+const DAY: u8 = 11;
+
 pub struct Monkey {
     items: VecDeque<u64>,
     inspection_count: u64,
-    inspect_op: fn(u64) -> u64,
-    pass_op: fn(u64) -> usize,
+    inspect_op: Box<dyn Fn(u64) -> u64>,
+    pass_op: Box<dyn Fn(u64) -> usize>,
+    divisor: u64,
+}
+
+impl Monkey {
+    fn parse(first: usize, s: &str, monkey_count: usize) -> Result<Self> {
+        let mut lines = parse::lines_from(DAY, first, s);
+        let mut next_line = |expected: &str| {
+            lines
+                .next()
+                .ok_or_else(|| {
+                    let end = first + s.lines().count();
+                    Line::new(DAY, end, "").error(format!("{expected:?}"))
+                })
+                .and_then(|mut l| l.tag(expected).map(|_| l))
+        };
+        let mut l = next_line("Monkey ")?;
+        l.int::<usize>()?;
+        l.tag(":")?;
+        l.end()?;
+        let mut l = next_line("  Starting items: ")?;
+        let mut items = VecDeque::from([l.int()?]);
+        while l.try_tag(", ") {
+            items.push_back(l.int()?);
+        }
+        l.end()?;
+        let mut l = next_line("  Operation: new = old ")?;
+        let op = l.char_map("* or +", |c| ['*', '+'].contains(&c).then_some(c))?;
+        l.tag(" ")?;
+        let inspect_op: Box<dyn Fn(u64) -> u64> = match (op, l.try_tag("old")) {
+            ('*', true) => Box::new(|val| val * val),
+            ('*', false) => {
+                let n: u64 = l.int()?;
+                Box::new(move |val| val * n)
+            }
+            _ => {
+                let n: u64 = l.int()?;
+                Box::new(move |val| val + n)
+            }
+        };
+        l.end()?;
+        let mut l = next_line("  Test: divisible by ")?;
+        let at = l.clone();
+        let divisor = l.int()?;
+        if divisor == 0 {
+            return Err(at.error("a non-zero divisor"));
+        }
+        l.end()?;
+        let mut target = |expected: &str| -> Result<usize> {
+            let mut l = next_line(expected)?;
+            let at = l.clone();
+            let n = l.int()?;
+            if n >= monkey_count {
+                return Err(at.error(format!("a monkey below {monkey_count}")));
+            }
+            l.end()?;
+            Ok(n)
+        };
+        let if_true = target("    If true: throw to monkey ")?;
+        let if_false = target("    If false: throw to monkey ")?;
+        Ok(Self {
+            items,
+            inspection_count: 0,
+            inspect_op,
+            pass_op: Box::new(move |worry| {
+                if worry % divisor == 0 {
+                    if_true
+                } else {
+                    if_false
+                }
+            }),
+            divisor,
+        })
+    }
 }
 
-pub fn part_1(mut monkeys: Vec<Monkey>) -> u64 {
+fn parse_monkeys(input: &str) -> Result<Vec<Monkey>> {
+    let monkey_count = parse::blocks(input).count();
+    let monkeys: Vec<_> = parse::blocks(input)
+        .map(|(first, block)| Monkey::parse(first, block, monkey_count))
+        .collect::<Result<_>>()?;
+    if monkeys.len() < 2 {
+        return Err(parse::missing(DAY, input, "at least two monkeys"));
+    }
+    Ok(monkeys)
+}
+
+pub fn part_1(input: &str) -> Result<u64> {
+    let mut monkeys = parse_monkeys(input)?;
     for _round in 0..20 {
         for i in 0..monkeys.len() {
             while let Some(item) = monkeys[i].items.pop_front() {
@@ -20,10 +112,12 @@ pub fn part_1(mut monkeys: Vec<Monkey>) -> u64 {
     }
     let len = monkeys.len();
     monkeys.select_nth_unstable_by_key(len - 2, |monkey| monkey.inspection_count);
-    monkeys[monkeys.len() - 2].inspection_count * monkeys[monkeys.len() - 1].inspection_count
+    Ok(monkeys[monkeys.len() - 2].inspection_count * monkeys[monkeys.len() - 1].inspection_count)
 }
 
-pub fn part_2(mut monkeys: Vec<Monkey>, modulo: u64) -> u64 {
+pub fn part_2(input: &str) -> Result<u64> {
+    let mut monkeys = parse_monkeys(input)?;
+    let modulo: u64 = monkeys.iter().map(|monkey| monkey.divisor).product();
     for _round in 0..10_000 {
         for i in 0..monkeys.len() {
             while let Some(item) = monkeys[i].items.pop_front() {
@@ -36,113 +130,40 @@ pub fn part_2(mut monkeys: Vec<Monkey>, modulo: u64) -> u64 {
     }
     let len = monkeys.len();
     monkeys.select_nth_unstable_by_key(len - 2, |monkey| monkey.inspection_count);
-    monkeys[len - 2].inspection_count * monkeys[len - 1].inspection_count
+    Ok(monkeys[len - 2].inspection_count * monkeys[len - 1].inspection_count)
+}
+
+pub struct Day11;
+impl Solution for Day11 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+    fn part_1(input: &str) -> Result<u64> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u64> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day11::*;
+    use crate::manifest::check;
 
     #[test]
     fn example11_day_part1() {
-        assert_eq!(part_1(example_monkeys()), 10605);
+        check(DAY, 1, "example11");
     }
     #[test]
     fn day11_part1() {
-        assert_eq!(part_1(real_monkeys()), 110220);
+        check(DAY, 1, "day11");
     }
     #[test]
     fn example11_part2() {
-        assert_eq!(part_2(example_monkeys(), 23 * 19 * 13 * 17), 2713310158);
+        check(DAY, 2, "example11");
     }
     #[test]
     fn day11_part2() {
-        assert_eq!(
-            part_2(real_monkeys(), 13 * 19 * 11 * 17 * 3 * 7 * 5 * 2),
-            19457438264
-        );
-    }
-
-    fn example_monkeys() -> Vec<Monkey> {
-        [
-            Monkey {
-                items: [79, 98].into(),
-                inspection_count: 0,
-                inspect_op: |val| val * 19,
-                pass_op: |worry| if worry % 23 == 0 { 2 } else { 3 },
-            },
-            Monkey {
-                items: [54, 65, 75, 74].into(),
-                inspection_count: 0,
-                inspect_op: |val| val + 6,
-                pass_op: |worry| if worry % 19 == 0 { 2 } else { 0 },
-            },
-            Monkey {
-                items: [79, 60, 97].into(),
-                inspection_count: 0,
-                inspect_op: |val| val * val,
-                pass_op: |worry| if worry % 13 == 0 { 1 } else { 3 },
-            },
-            Monkey {
-                items: [74].into(),
-                inspection_count: 0,
-                inspect_op: |val| val + 3,
-                pass_op: |worry| if worry % 17 == 0 { 0 } else { 1 },
-            },
-        ]
-        .into()
-    }
-    fn real_monkeys() -> Vec<Monkey> {
-        [
-            Monkey {
-                items: [53, 89, 62, 57, 74, 51, 83, 97].into(),
-                inspection_count: 0,
-                inspect_op: |val| val * 3,
-                pass_op: |worry| if worry % 13 == 0 { 1 } else { 5 },
-            },
-            Monkey {
-                items: [85, 94, 97, 92, 56].into(),
-                inspection_count: 0,
-                inspect_op: |val| val + 2,
-                pass_op: |worry| if worry % 19 == 0 { 5 } else { 2 },
-            },
-            Monkey {
-                items: [86, 82, 82].into(),
-                inspection_count: 0,
-                inspect_op: |val| val + 1,
-                pass_op: |worry| if worry % 11 == 0 { 3 } else { 4 },
-            },
-            Monkey {
-                items: [94, 68].into(),
-                inspection_count: 0,
-                inspect_op: |val| val + 5,
-                pass_op: |worry| if worry % 17 == 0 { 7 } else { 6 },
-            },
-            Monkey {
-                items: [83, 62, 74, 58, 96, 68, 85].into(),
-                inspection_count: 0,
-                inspect_op: |val| val + 4,
-                pass_op: |worry| if worry % 3 == 0 { 3 } else { 6 },
-            },
-            Monkey {
-                items: [50, 68, 95, 82].into(),
-                inspection_count: 0,
-                inspect_op: |val| val + 8,
-                pass_op: |worry| if worry % 7 == 0 { 2 } else { 4 },
-            },
-            Monkey {
-                items: [75].into(),
-                inspection_count: 0,
-                inspect_op: |val| val * 7,
-                pass_op: |worry| if worry % 5 == 0 { 7 } else { 0 },
-            },
-            Monkey {
-                items: [92, 52, 85, 89, 68, 82].into(),
-                inspection_count: 0,
-                inspect_op: |val| val * val,
-                pass_op: |worry| if worry % 2 == 0 { 0 } else { 1 },
-            },
-        ]
-        .into()
+        check(DAY, 2, "day11");
     }
 }
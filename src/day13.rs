@@ -1,5 +1,11 @@
+use crate::{
+    error::{Error, Result},
+    parse::{self, Line},
+    solution::Solution,
+};
 use std::cmp::Ordering;
-use std::iter::Peekable;
+
+const DAY: u8 = 13;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 enum Node {
@@ -8,35 +14,34 @@ enum Node {
 }
 
 impl Node {
-    fn parse(s: &str) -> Self {
-        parse_recursive(&mut s.chars().peekable()).unwrap()
+    fn parse(mut l: Line) -> Result<Self> {
+        let node = parse_recursive(&mut l)?;
+        l.end()?;
+        Ok(node)
+    }
+    fn divider(n: i32) -> Self {
+        Node::List([Node::List([Node::Val(n)].into())].into())
     }
 }
 
-fn parse_recursive<I: Iterator<Item = char>>(chars: &mut Peekable<I>) -> Option<Node> {
-    if matches!(chars.peek(), Some('[')) {
-        chars.next().unwrap();
+fn parse_recursive(l: &mut Line) -> Result<Node> {
+    if l.try_tag("[") {
         let mut list = Vec::new();
-        while let Some(node) = parse_recursive(chars) {
-            list.push(node);
-            if matches!(chars.peek(), Some(',')) {
-                chars.next();
-            }
+        if l.try_tag("]") {
+            return Ok(Node::List(list));
         }
-        Node::List(list).into()
-    } else if matches!(chars.peek(), Some(']')) {
-        chars.next();
-        None
-    } else {
-        let mut num = String::new();
         loop {
-            if matches!(chars.peek(), Some(c) if c.is_ascii_digit()) {
-                num.push(chars.next().unwrap());
-            } else {
+            list.push(parse_recursive(l)?);
+            if l.try_tag("]") {
                 break;
             }
+            if !l.try_tag(",") {
+                return Err(l.error("\",\" or \"]\""));
+            }
         }
-        Node::Val(num.parse().unwrap()).into()
+        Ok(Node::List(list))
+    } else {
+        Ok(Node::Val(l.int()?))
     }
 }
 
@@ -70,58 +75,76 @@ impl Ord for Node {
     }
 }
 
-pub fn part_1(input: &str) -> usize {
+pub fn part_1(input: &str) -> Result<usize> {
     let mut count = 0;
-    for (i, pair) in input.split("\n\n").enumerate() {
-        let mut lines = pair.lines();
-        let a = Node::parse(lines.next().unwrap());
-        let b = Node::parse(lines.next().unwrap());
+    for (i, (first, pair)) in parse::blocks(input).enumerate() {
+        let mut lines = parse::lines_from(DAY, first, pair);
+        let mut next_packet = || {
+            let l = lines.next().ok_or_else(|| {
+                Line::new(DAY, first + pair.lines().count(), "").error("a packet")
+            })?;
+            Node::parse(l)
+        };
+        let a = next_packet()?;
+        let b = next_packet()?;
         match a.partial_cmp(&b).unwrap() {
             Ordering::Less => count += i + 1,
-            Ordering::Equal => panic!(),
+            Ordering::Equal => {
+                return Err(Error::no_answer(
+                    DAY,
+                    format!("packets on line {first} are equal"),
+                ))
+            }
             Ordering::Greater => (),
         }
     }
-    count
+    Ok(count)
 }
-pub fn part_2(input: &str) -> usize {
-    let mut packets: Vec<_> = input
-        .split("\n\n")
-        .flat_map(str::lines)
+pub fn part_2(input: &str) -> Result<usize> {
+    let mut packets: Vec<_> = parse::lines(DAY, input)
+        .filter(|l| !l.is_done())
         .map(Node::parse)
-        .collect();
-    let n2 = Node::parse("[[2]]");
-    let n6 = Node::parse("[[6]]");
+        .collect::<Result<_>>()?;
+    let n2 = Node::divider(2);
+    let n6 = Node::divider(6);
     packets.push(n2.clone());
     packets.push(n6.clone());
     packets.sort_unstable();
     let pos2 = packets.binary_search(&n2);
     let pos6 = packets.binary_search(&n6);
-    (pos2.unwrap() + 1) * (pos6.unwrap() + 1)
+    Ok((pos2.unwrap() + 1) * (pos6.unwrap() + 1))
+}
+
+pub struct Day13;
+impl Solution for Day13 {
+    type Answer1 = usize;
+    type Answer2 = usize;
+    fn part_1(input: &str) -> Result<usize> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<usize> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day13::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example13_day_part1() {
-        let input = fs::read_to_string("input/example13").unwrap();
-        assert_eq!(part_1(&input), 13);
+        check(DAY, 1, "example13");
     }
     #[test]
     fn day13_part1() {
-        let input = fs::read_to_string("input/day13").unwrap();
-        assert_eq!(part_1(&input), 6395);
+        check(DAY, 1, "day13");
     }
     #[test]
     fn example13_part2() {
-        let input = fs::read_to_string("input/example13").unwrap();
-        assert_eq!(part_2(&input), 140);
+        check(DAY, 2, "example13");
     }
     #[test]
     fn day13_part2() {
-        let input = fs::read_to_string("input/day13").unwrap();
-        assert_eq!(part_2(&input), 24921);
+        check(DAY, 2, "day13");
     }
 }
@@ -1,24 +1,54 @@
-use std::{cmp, collections::HashSet, iter};
-enum Part {
+use crate::{
+    error::Result,
+    parse::{self, Line},
+    render::{self, Canvas, Color, Pixel, Renderer},
+    simulation::Simulation,
+    solution::Solution,
+};
+use std::{cmp, collections::HashSet};
+
+const DAY: u8 = 14;
+
+pub enum Part {
+    /// Sand falls into the abyss below the lowest rock.
     One,
+    /// There's a floor two below the lowest rock.
     Two,
 }
-struct SandMap {
+
+/// Cave filling up with sand, one unit per step.
+pub struct SandMap {
     source: (i32, i32),
     map: HashSet<(i32, i32)>,
     bottom: i32,
     part: Part,
+    /// Where each unit of sand came to rest, in order.
+    sand: Vec<(i32, i32)>,
+    done: bool,
+    ticks: usize,
 }
 impl SandMap {
-    fn from_str(s: &str, part: Part) -> Self {
+    pub fn from_str(s: &str, part: Part) -> Result<Self> {
         let mut map: HashSet<(i32, i32)> = HashSet::new();
         let mut bottom = -1;
-        for l in s.lines() {
-            let mut points = l.split(" -> ").map(|p: &str| {
-                p.split_once(',')
-                    .map(|(x, y)| (x.parse().unwrap(), y.parse().unwrap()))
-                    .unwrap()
-            });
+        for mut l in parse::lines(DAY, s) {
+            let point = |l: &mut Line| -> Result<(i32, i32)> {
+                let x = l.int()?;
+                l.tag(",")?;
+                Ok((x, l.int()?))
+            };
+            let mut points = vec![point(&mut l)?];
+            while !l.is_done() {
+                l.tag(" -> ")?;
+                let at = l.clone();
+                let p = point(&mut l)?;
+                let prev = points[points.len() - 1];
+                if p.0 != prev.0 && p.1 != prev.1 {
+                    return Err(at.error("a point in line with the previous one"));
+                }
+                points.push(p);
+            }
+            let mut points = points.into_iter();
             let mut prev: (i32, i32) = points.next().unwrap();
             bottom = cmp::max(bottom, prev.1);
             for next in points {
@@ -36,15 +66,23 @@ impl SandMap {
                 prev = next;
             }
         }
-        Self {
+        // Without rocks there's no floor either, and sand would pile up forever.
+        if map.is_empty() {
+            return Err(parse::missing(DAY, s, "a rock path"));
+        }
+        Ok(Self {
             source: (500, 0),
             map,
             bottom,
             part,
-        }
+            sand: Vec::new(),
+            done: false,
+            ticks: 0,
+        })
     }
 
-    fn step(&mut self) -> bool {
+    /// Drops one unit of sand, returning whether more can follow.
+    fn drop_sand(&mut self) -> bool {
         let mut p = self.source;
         loop {
             if matches!(self.part, Part::One) && p.1 > self.bottom {
@@ -53,6 +91,7 @@ impl SandMap {
             } else if p.1 + 1 == self.bottom + 2 {
                 // fell to inf floor
                 self.map.insert(p);
+                self.sand.push(p);
                 break true;
             }
             if let Some(fall) = [0, -1, 1]
@@ -64,45 +103,166 @@ impl SandMap {
             } else {
                 // at rest
                 self.map.insert(p);
+                self.sand.push(p);
                 break p != self.source;
             }
         }
     }
 }
 
-pub fn part_1(input: &str) -> usize {
-    let mut sand_map = SandMap::from_str(input, Part::One);
-    iter::from_fn(|| sand_map.step().then_some(())).count()
+impl Simulation for SandMap {
+    /// The cave as in the puzzle text, just wide enough for everything in it.
+    type State = Canvas;
+    fn step(&mut self) {
+        if !self.done {
+            self.done = !self.drop_sand();
+            self.ticks += 1;
+        }
+    }
+    fn is_done(&self) -> bool {
+        self.done
+    }
+    fn snapshot(&self) -> Canvas {
+        let (mut min, mut max) = (self.source, self.source);
+        for &(x, y) in &self.map {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        if matches!(self.part, Part::Two) {
+            max.1 = self.bottom + 2;
+        }
+        let to_canvas = |(x, y): (i32, i32)| ((x - min.0) as usize, (y - min.1) as usize);
+        let width = (max.0 - min.0 + 1) as usize;
+        let mut canvas = render::canvas(width, (max.1 - min.1 + 1) as usize);
+        for &p in &self.map {
+            canvas[to_canvas(p)] = Pixel::new('#', Color::GRAY);
+        }
+        for &p in &self.sand {
+            canvas[to_canvas(p)] = Pixel::new('o', Color::YELLOW);
+        }
+        if matches!(self.part, Part::Two) {
+            for x in 0..width {
+                canvas[(x, (max.1 - min.1) as usize)] = Pixel::new('#', Color::GRAY);
+            }
+        }
+        if self.sand.last() != Some(&self.source) {
+            canvas[to_canvas(self.source)] = Pixel::new('+', Color::RED);
+        }
+        canvas
+    }
+    fn ticks(&self) -> usize {
+        self.ticks
+    }
+}
+
+pub fn part_1(input: &str) -> Result<usize> {
+    let mut sand_map = SandMap::from_str(input, Part::One)?;
+    sand_map.run(usize::MAX);
+    Ok(sand_map.sand.len())
 }
 
-pub fn part_2(input: &str) -> usize {
-    let mut sand_map = SandMap::from_str(input, Part::Two);
-    let sand = iter::from_fn(|| sand_map.step().then_some(())).count();
-    sand + 1
+pub fn part_2(input: &str) -> Result<usize> {
+    let mut sand_map = SandMap::from_str(input, Part::Two)?;
+    sand_map.run(usize::MAX);
+    Ok(sand_map.sand.len())
+}
+
+/// One frame per unit of sand coming to rest.
+pub fn visualize(input: &str, part: u8, renderer: &mut dyn Renderer) -> Result<()> {
+    let part = if part == 1 { Part::One } else { Part::Two };
+    let mut sand_map = SandMap::from_str(input, part)?;
+    let floor = sand_map.bottom + 2;
+    let (mut min_x, mut max_x) = (sand_map.source.0, sand_map.source.0);
+    for &(x, _) in &sand_map.map {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+    }
+    if matches!(sand_map.part, Part::Two) {
+        min_x = min_x.min(sand_map.source.0 - floor);
+        max_x = max_x.max(sand_map.source.0 + floor);
+    }
+    let to_canvas = |(x, y): (i32, i32)| ((x - min_x) as usize, y as usize);
+    let mut canvas = render::canvas((max_x - min_x + 1) as usize, floor as usize + 1);
+    for &p in &sand_map.map {
+        canvas[to_canvas(p)] = Pixel::new('#', Color::GRAY);
+    }
+    if matches!(sand_map.part, Part::Two) {
+        for x in min_x..=max_x {
+            canvas[to_canvas((x, floor))] = Pixel::new('#', Color::GRAY);
+        }
+    }
+    canvas[to_canvas(sand_map.source)] = Pixel::new('+', Color::RED);
+    renderer.frame(&canvas)?;
+    while !sand_map.is_done() {
+        let resting = sand_map.sand.len();
+        sand_map.step();
+        if let Some(&p) = sand_map.sand.get(resting) {
+            canvas[to_canvas(p)] = Pixel::new('o', Color::YELLOW);
+            renderer.frame(&canvas)?;
+        }
+    }
+    Ok(())
+}
+
+pub struct Day14;
+impl Solution for Day14 {
+    type Answer1 = usize;
+    type Answer2 = usize;
+    fn part_1(input: &str) -> Result<usize> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<usize> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day14::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example14_day_part1() {
-        let input = fs::read_to_string("input/example14").unwrap();
-        assert_eq!(part_1(&input), 24);
+        check(DAY, 1, "example14");
     }
     #[test]
     fn day14_part1() {
-        let input = fs::read_to_string("input/day14").unwrap();
-        assert_eq!(part_1(&input), 757);
+        check(DAY, 1, "day14");
     }
     #[test]
     fn example14_part2() {
-        let input = fs::read_to_string("input/example14").unwrap();
-        assert_eq!(part_2(&input), 93);
+        check(DAY, 2, "example14");
     }
     #[test]
     fn day14_part2() {
-        let input = fs::read_to_string("input/day14").unwrap();
-        assert_eq!(part_2(&input), 24943);
+        check(DAY, 2, "day14");
+    }
+    #[test]
+    fn sand_steps() {
+        let input = std::fs::read_to_string("input/example14").unwrap();
+        let mut sand_map = SandMap::from_str(&input, Part::One).unwrap();
+        sand_map.run(5);
+        assert_eq!(
+            sand_map.snapshot().render(|p| p.ch),
+            "......+...\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             ....#...##\n\
+             ....#...#.\n\
+             ..###...#.\n\
+             ......o.#.\n\
+             ....oooo#.\n\
+             #########.\n"
+        );
+        assert_eq!((sand_map.run(100), sand_map.ticks()), (20, 25));
+        assert!(sand_map.is_done());
+    }
+    #[test]
+    fn no_rocks() {
+        let err = part_2("").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "day 14, line 1, column 1: expected a rock path, found nothing"
+        );
     }
 }
@@ -1,4 +1,13 @@
-use itertools::Itertools;
+use crate::{
+    error::{Error, Result},
+    parallel,
+    parse::{self, Line},
+    solution::Solution,
+    strategy::Strategy,
+};
+use std::collections::HashSet;
+
+const DAY: u8 = 15;
 
 type Pos = (i32, i32);
 struct Sensor {
@@ -8,25 +17,24 @@ struct Sensor {
 }
 
 impl Sensor {
-    fn parse(s: &str) -> Self {
-        let (x, rest) = s
-            .trim_start_matches("Sensor at x=")
-            .split_once(',')
-            .unwrap();
-        let (y, rest) = rest.trim_start_matches(" y=").split_once(':').unwrap();
-        let (bx, rest) = rest
-            .trim_start_matches(" closest beacon is at x=")
-            .split_once(',')
-            .unwrap();
-        let by = rest.trim_start_matches(" y=");
-        let pos: Pos = (x.parse().unwrap(), y.parse().unwrap());
-        let beacon_pos: Pos = (bx.parse().unwrap(), by.parse().unwrap());
+    fn parse(mut l: Line) -> Result<Self> {
+        l.tag("Sensor at x=")?;
+        let x = l.int()?;
+        l.tag(", y=")?;
+        let y = l.int()?;
+        l.tag(": closest beacon is at x=")?;
+        let bx = l.int()?;
+        l.tag(", y=")?;
+        let by = l.int()?;
+        l.end()?;
+        let pos: Pos = (x, y);
+        let beacon_pos: Pos = (bx, by);
         let radius = pos.0.abs_diff(beacon_pos.0) as i32 + pos.1.abs_diff(beacon_pos.1) as i32;
-        Self {
+        Ok(Self {
             pos,
             radius,
             beacon_pos,
-        }
+        })
     }
     fn in_range(&self, p: Pos) -> bool {
         if p == self.beacon_pos {
@@ -95,56 +103,113 @@ impl Iterator for Perimeter {
     }
 }
 
-pub fn part_1(input: &str, row: i32) -> u32 {
-    let sensors = input.lines().map(Sensor::parse).collect_vec();
+fn parse_sensors(input: &str) -> Result<Vec<Sensor>> {
+    parse::lines(DAY, input).map(Sensor::parse).collect()
+}
+
+/// Positions in `row` no beacon can be at.
+pub fn part_1(input: &str, row: i32) -> Result<u32> {
+    let sensors = parse_sensors(input)?;
+    let mut covered: Vec<(i32, i32)> = sensors
+        .iter()
+        .filter_map(|s| {
+            let reach = s.radius - s.pos.1.abs_diff(row) as i32;
+            (reach >= 0).then_some((s.pos.0 - reach, s.pos.0 + reach))
+        })
+        .collect();
+    covered.sort_unstable();
+    let mut count = 0;
+    let mut end = i32::MIN;
+    for (from, to) in covered {
+        if to > end {
+            count += (to - from.max(end + 1) + 1) as u32;
+            end = to;
+        }
+    }
+    let beacons: HashSet<Pos> = sensors
+        .iter()
+        .map(|s| s.beacon_pos)
+        .filter(|b| b.1 == row)
+        .collect();
+    Ok(count - beacons.len() as u32)
+}
+
+/// Checks every position in the row against every sensor. Kept as a reference
+/// for `part_1`.
+pub fn part_1_scan(input: &str, row: i32) -> Result<u32> {
+    let sensors = parse_sensors(input)?;
     let (min_x, max_x) = sensors
         .iter()
         .map(Sensor::x_range)
         .fold((i32::MAX, i32::MIN), |acc, (min, max)| {
             (acc.0.min(min), acc.1.max(max))
         });
-    (min_x..max_x)
-        .into_iter()
+    let beacons: HashSet<Pos> = sensors.iter().map(|s| s.beacon_pos).collect();
+    Ok((min_x..=max_x)
+        .filter(|&x| !beacons.contains(&(x, row)))
         .map(|x| sensors.iter().any(|s| s.in_range((x, row))) as u32)
-        .sum()
+        .sum())
 }
-pub fn part_2(input: &str) -> i64 {
-    let sensors = input.lines().map(Sensor::parse).collect_vec();
-    let inside_box = |p: Pos| (0..=4_000_000).contains(&p.0) && (0..=4_000_000).contains(&p.1);
-    let mut distress_pos = None;
-    'outer: for i in 0..sensors.len() {
-        let perimeter = sensors[i].perimeter();
-        for p in perimeter {
-            if !inside_box(p) {
-                continue;
-            }
-            if sensors.iter().all(|s| !s.in_range(p)) {
-                distress_pos = Some(p);
-                break 'outer;
-            }
-        }
+pub fn part_2(input: &str) -> Result<i64> {
+    let sensors = parse_sensors(input)?;
+    let inside_box = |p: &Pos| (0..=4_000_000).contains(&p.0) && (0..=4_000_000).contains(&p.1);
+    // The one uncovered position is next to some sensor's range; each is walked around
+    // separately.
+    let distress_pos = parallel::find_map(&sensors, |sensor| {
+        sensor
+            .perimeter()
+            .filter(inside_box)
+            .find(|&p| sensors.iter().all(|s| !s.in_range(p)))
+    });
+    let (x, y) = distress_pos.ok_or_else(|| Error::no_answer(DAY, "no uncovered position"))?;
+    Ok(4000000 * x as i64 + y as i64)
+}
+
+pub const STRATEGIES: [Strategy; 2] = [
+    Strategy {
+        name: "intervals",
+        part: 1,
+        run: |input| part_1(input, 2_000_000).map(Into::into),
+    },
+    Strategy {
+        name: "scan",
+        part: 1,
+        run: |input| part_1_scan(input, 2_000_000).map(Into::into),
+    },
+];
+
+pub struct Day15;
+impl Solution for Day15 {
+    type Answer1 = u32;
+    type Answer2 = i64;
+    fn part_1(input: &str) -> Result<u32> {
+        part_1(input, 2_000_000)
+    }
+    fn part_2(input: &str) -> Result<i64> {
+        part_2(input)
     }
-    let (x, y) = distress_pos.unwrap();
-    4000000 * x as i64 + y as i64
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day15::*;
+    use crate::manifest::check;
     use std::fs;
     #[test]
     fn example15_day_part1() {
         let input = fs::read_to_string("input/example15").unwrap();
-        assert_eq!(part_1(&input, 10), 26);
+        assert_eq!(part_1(&input, 10).unwrap(), 26);
+        assert_eq!(part_1_scan(&input, 10).unwrap(), 26);
+        let edge = "Sensor at x=0, y=2000000: closest beacon is at x=0, y=2000002\n";
+        assert_eq!(part_1(edge, 2_000_000).unwrap(), 5);
+        assert_eq!(part_1_scan(edge, 2_000_000).unwrap(), 5);
     }
     #[test]
     fn day15_part1() {
-        let input = fs::read_to_string("input/day15").unwrap();
-        assert_eq!(part_1(&input, 2_000_000), 5716881);
+        check(DAY, 1, "day15");
     }
     #[test]
     fn day15_part2() {
-        let input = fs::read_to_string("input/day15").unwrap();
-        assert_eq!(part_2(&input), 10852583132904);
+        check(DAY, 2, "day15");
     }
 }
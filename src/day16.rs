@@ -1,84 +1,104 @@
-use bitvec::prelude::*;
+use crate::{
+    budget::{Bounded, Budget},
+    error::{Error, Result},
+    parallel,
+    parse::{self, Line},
+    search,
+    solution::{Answer, Solution},
+    stats::{self, SearchStats},
+    strategy::Strategy,
+};
 use im::HashSet;
-use std::{cell::Cell, collections::HashMap};
+use std::{cell::Cell, cmp::Reverse, collections::HashMap};
 type Id = u16;
-const AA_ID: Id = b'A' as u16 * b'A' as u16;
+const AA_ID: Id = u16::from_be_bytes([b'A', b'A']);
+const DAY: u8 = 16;
 
-fn get_flow_and_travel_maps(s: &str) -> (HashMap<Id, u32>, HashMap<Id, HashMap<Id, u32>>) {
-    let mut flow_map = HashMap::new();
-    let mut travel_map: HashMap<Id, HashMap<Id, u32>> = HashMap::new();
-    for l in s.lines() {
-        let (valve, rest) = l.trim_start_matches("Valve ").split_once(' ').unwrap();
-        let (flow_rate, rest) = rest
-            .trim_start_matches("has flow rate=")
-            .split_once(';')
-            .unwrap();
-        let other_valves = rest
-            .trim_start_matches(" tunnels lead to valves ")
-            .trim_start_matches(" tunnel leads to valve ");
-        let other_valves = other_valves.split(", ");
-        let string_to_id = |s: &str| s.bytes().map(|b| b as u16).product();
-        let valve_id = string_to_id(valve);
-        let flow_rate = flow_rate.parse().unwrap();
-        flow_map.insert(valve_id, flow_rate);
-
-        for ov in other_valves {
-            let other_id = string_to_id(ov);
-            travel_map.entry(valve_id).or_default().insert(other_id, 1);
-            travel_map.entry(other_id).or_default().insert(valve_id, 1);
+fn parse_valve(l: &mut Line) -> Result<Id> {
+    let at = l.clone();
+    match l.word()?.as_bytes() {
+        &[a, b] if a.is_ascii_uppercase() && b.is_ascii_uppercase() => {
+            Ok(u16::from_be_bytes([a, b]))
         }
+        _ => Err(at.error("a two letter valve name")),
     }
-    (flow_map, travel_map)
-}
-
-fn expand_travel_map(map: &mut HashMap<Id, HashMap<Id, u32>>, flow: &HashMap<Id, u32>) {
-    loop {
-        let mut improved = false;
-        for valve in flow.keys() {
-            for other in flow.keys() {
-                if valve == other {
-                    continue;
-                }
-                let mut shortest: u32 = map[valve].get(other).cloned().unwrap_or(u32::MAX);
-                let mut beat_current_best = false;
-                for mid in map[valve].iter() {
-                    let candidate = map[mid.0]
-                        .get(other)
-                        .map(|cost| mid.1 + cost)
-                        .unwrap_or(u32::MAX);
-                    if candidate < shortest {
-                        shortest = candidate;
-                        beat_current_best = true;
-                    }
-                }
-                if beat_current_best {
-                    map.get_mut(valve).unwrap().insert(*other, shortest);
-                    map.get_mut(other).unwrap().insert(*valve, shortest);
-                    improved = true;
-                }
+}
+
+type FlowMap = HashMap<Id, u32>;
+type TunnelMap = HashMap<Id, Vec<Id>>;
+type TravelMap = HashMap<(Id, Id), u32>;
+fn get_flow_and_tunnel_maps(s: &str) -> Result<(FlowMap, TunnelMap)> {
+    let mut flow_map = HashMap::new();
+    let mut tunnel_map = TunnelMap::new();
+    for mut l in parse::lines(DAY, s) {
+        l.tag("Valve ")?;
+        let valve_id = parse_valve(&mut l)?;
+        l.tag(" has flow rate=")?;
+        let flow_rate = l.int()?;
+        l.tag(";")?;
+        if !l.try_tag(" tunnels lead to valves ") {
+            l.tag(" tunnel leads to valve ")?;
+        }
+        flow_map.insert(valve_id, flow_rate);
+        loop {
+            let other_id = parse_valve(&mut l)?;
+            tunnel_map.entry(valve_id).or_default().push(other_id);
+            tunnel_map.entry(other_id).or_default().push(valve_id);
+            if !l.try_tag(", ") {
+                break;
             }
         }
-        if !improved {
-            break;
+        l.end()?;
+    }
+    if !flow_map.contains_key(&AA_ID) {
+        return Err(parse::missing(DAY, s, "valve AA"));
+    }
+    Ok((flow_map, tunnel_map))
+}
+
+/// Minutes to walk between every pair of valves.
+fn get_travel_map(tunnels: &TunnelMap, flow: &FlowMap) -> TravelMap {
+    let mut travel_map = TravelMap::new();
+    for &valve in flow.keys() {
+        let neighbors = |v: &Id| tunnels.get(v).into_iter().flatten().copied();
+        for (other, time) in search::bfs([valve], neighbors, |_| false).dist {
+            travel_map.insert((valve, other), time as u32);
         }
     }
+    travel_map
+}
+
+fn count(stats: &Cell<SearchStats>, f: impl FnOnce(&mut SearchStats)) {
+    let mut s = stats.get();
+    f(&mut s);
+    stats.set(s);
 }
-struct DfsFlowPlan {
+
+struct DfsFlowPlan<'a> {
     flow_map: HashMap<Id, u32>,
-    travel_map: HashMap<(Id, Id), u32>,
+    travel_map: TravelMap,
+    budget: &'a Budget,
+    stats: Cell<SearchStats>,
 }
 
-impl DfsFlowPlan {
+impl DfsFlowPlan<'_> {
     fn search(&self, current: Id, mut visited: HashSet<Id>, pressure: u32, time_left: u32) -> u32 {
         visited.insert(current);
+        if !self.budget.spend() {
+            return pressure;
+        }
+        count(&self.stats, |s| s.expand(visited.len() - 1));
         let mut best_pressure = pressure;
         for (next, flow) in self.flow_map.iter() {
             if *flow == 0 || visited.contains(next) {
                 continue;
             }
-            let time_cost = self.travel_map[&(current, *next)] + 1;
+            let Some(time_cost) = self.travel_map.get(&(current, *next)).map(|t| t + 1) else {
+                continue;
+            };
             let value = time_left.saturating_sub(time_cost) * self.flow_map[next];
             if value == 0 {
+                count(&self.stats, SearchStats::prune);
                 continue;
             }
             let candidate = self.search(
@@ -93,144 +113,271 @@ impl DfsFlowPlan {
     }
 }
 
-pub fn part_1(input: &str) -> u32 {
-    let (flow_map, mut travel_map) = get_flow_and_travel_maps(input);
-    expand_travel_map(&mut travel_map, &flow_map);
-    let travel_map: HashMap<(Id, Id), u32> = travel_map
-        .iter()
-        .flat_map(|(&id_a, inner)| inner.iter().map(move |(id_b, time)| ((id_a, *id_b), *time)))
-        .collect();
+pub fn part_1(input: &str) -> Result<u32> {
+    part_1_within(input, &Budget::unlimited()).map(|b| b.value)
+}
+
+pub fn part_1_within(input: &str, budget: &Budget) -> Result<Bounded<u32>> {
+    let (flow_map, tunnel_map) = get_flow_and_tunnel_maps(input)?;
+    let travel_map = get_travel_map(&tunnel_map, &flow_map);
     let dfs = DfsFlowPlan {
         travel_map,
         flow_map,
+        budget,
+        stats: Cell::default(),
     };
-    dfs.search(AA_ID, HashSet::new(), 0, 30)
+    let best = dfs.search(AA_ID, HashSet::new(), 0, 30);
+    stats::report("plan", dfs.stats.get());
+    Ok(budget.finish(best))
 }
 
 ///////////////// part 2
-struct DfsFlowPlanPart2<'a> {
-    flow_map: &'a HashMap<Id, u32>,
-    travel_map: &'a HashMap<(Id, Id), u32>,
-    valve_map: &'a HashMap<Id, u8>,
-    best: Cell<u32>,
+/// Most pressure one actor can release for each set of valves it opens, as a bit mask.
+type BestBySet = HashMap<u64, u32>;
+
+struct SetPlan<'a> {
+    flow_map: &'a FlowMap,
+    travel_map: &'a TravelMap,
+    valve_bits: &'a HashMap<Id, u64>,
+    budget: &'a Budget,
+    stats: Cell<SearchStats>,
 }
 
-impl<'a> DfsFlowPlanPart2<'a> {
+impl SetPlan<'_> {
     fn search(
         &self,
-        current_id: Id,
-        mut visited: bitvec::array::BitArray,
-        score: u32,
-        other_score: Option<u32>,
+        current: Id,
+        opened: u64,
+        pressure: u32,
         time_left: u32,
-        memo: &mut std::collections::HashSet<(Id, u32, Option<u32>, bitvec::array::BitArray)>,
+        best: &mut BestBySet,
     ) {
-        let valve_index = self.valve_map[&current_id];
-        visited.set(valve_index as usize, true);
-        if !memo.insert((current_id, time_left, other_score, visited)) {
+        let entry = best.entry(opened).or_default();
+        *entry = pressure.max(*entry);
+        if !self.budget.spend() {
             return;
         }
-        if time_left == 0 {
-            if let Some(other_score) = other_score {
-                // end of elephant plan, add up the score
-                let total_score = score + other_score;
-                if total_score > self.best.get() {
-                    self.best.set(total_score);
-                }
-                return;
-            } else {
-                //start elephant plan
-                let elephant_dfs = DfsFlowPlanPart2 {
-                    flow_map: self.flow_map,
-                    travel_map: self.travel_map,
-                    valve_map: self.valve_map,
-                    best: Cell::new(0),
-                };
-                elephant_dfs.search(AA_ID, visited, 0, Some(score), 26, memo);
-                if elephant_dfs.best > self.best {
-                    self.best.set(elephant_dfs.best.get());
-                }
-                return;
-            }
-        }
+        count(&self.stats, |s| s.expand(opened.count_ones() as usize));
         for (next, flow) in self.flow_map.iter() {
-            if *flow == 0 {
+            let bit = self.valve_bits[next];
+            if opened & bit != 0 {
                 continue;
             }
-            let next_index = self.valve_map[next];
-            if *visited.get(next_index as usize).unwrap() {
+            let Some(time_cost) = self.travel_map.get(&(current, *next)).map(|t| t + 1) else {
                 continue;
-            }
-            let time_cost = self.travel_map[&(current_id, *next)] + 1;
+            };
             if time_cost >= time_left {
+                count(&self.stats, SearchStats::prune);
                 continue;
             }
-            let value = flow * (time_left - time_cost);
+            let time_left = time_left - time_cost;
             self.search(
                 *next,
-                visited,
-                score + value,
-                other_score,
-                time_left - time_cost,
-                memo,
+                opened | bit,
+                pressure + flow * time_left,
+                time_left,
+                best,
             );
         }
-        self.search(current_id, visited, score, other_score, 0, memo);
     }
 }
 
-pub fn part_2(input: &str) -> u32 {
-    let (mut flow_map, mut travel_map) = get_flow_and_travel_maps(input);
-    expand_travel_map(&mut travel_map, &flow_map);
-    let travel_map: HashMap<(Id, Id), u32> = travel_map
-        .iter()
-        .flat_map(|(&id_a, inner)| inner.iter().map(move |(id_b, time)| ((id_a, *id_b), *time)))
-        .collect();
-    let valve_map = flow_map
+pub fn part_2(input: &str) -> Result<u32> {
+    part_2_within(input, &Budget::unlimited()).map(|b| b.value)
+}
+
+/// You and the elephant open disjoint sets of valves, so the answer is the best pair
+/// of disjoint sets one actor can open.
+pub fn part_2_within(input: &str, budget: &Budget) -> Result<Bounded<u32>> {
+    let (mut flow_map, tunnel_map) = get_flow_and_tunnel_maps(input)?;
+    let travel_map = get_travel_map(&tunnel_map, &flow_map);
+    flow_map.retain(|_, v| *v != 0);
+    if flow_map.len() > 64 {
+        return Err(Error::no_answer(DAY, "more than 64 valves with flow"));
+    }
+    let valve_bits: HashMap<Id, u64> = flow_map
         .keys()
         .enumerate()
-        .map(|(i, valve)| (*valve, i as u8))
+        .map(|(i, valve)| (*valve, 1 << i))
         .collect();
+    // Plans starting with different valves are searched in parallel.
+    let firsts: Vec<Id> = flow_map.keys().copied().collect();
+    let per_first = parallel::map(&firsts, |&first| {
+        let plan = SetPlan {
+            flow_map: &flow_map,
+            travel_map: &travel_map,
+            valve_bits: &valve_bits,
+            budget,
+            stats: Cell::default(),
+        };
+        let mut best = BestBySet::new();
+        if let Some(time_cost) = travel_map.get(&(AA_ID, first)).map(|t| t + 1) {
+            if time_cost < 26 {
+                let time_left = 26 - time_cost;
+                let pressure = flow_map[&first] * time_left;
+                plan.search(first, valve_bits[&first], pressure, time_left, &mut best);
+            }
+        }
+        stats::report("plan per valve set", plan.stats.get());
+        best
+    });
+    let mut best = BestBySet::from([(0, 0)]);
+    for (set, pressure) in per_first.into_iter().flatten() {
+        let entry = best.entry(set).or_default();
+        *entry = pressure.max(*entry);
+    }
+    // With the sets best first, the first disjoint one is the best partner.
+    let mut sets: Vec<(u64, u32)> = best.into_iter().collect();
+    sets.sort_unstable_by_key(|&(_, pressure)| Reverse(pressure));
+    let with_partner = parallel::map(&sets, |&(set, pressure)| {
+        sets.iter()
+            .find(|(other, _)| set & other == 0)
+            .map_or(pressure, |(_, other)| pressure + other)
+    });
+    Ok(budget.finish(with_partner.into_iter().max().unwrap_or(0)))
+}
+
+struct DfsFlowPlanPart2<'a> {
+    flow_map: &'a FlowMap,
+    travel_map: &'a TravelMap,
+    valve_bits: &'a HashMap<Id, u64>,
+    stats: Cell<SearchStats>,
+}
+
+/// Most pressure still to come from (valve, time left, opened valves, elephant's turn).
+type Memo = HashMap<(Id, u32, u64, bool), u32>;
+
+impl DfsFlowPlanPart2<'_> {
+    /// The most pressure left to release: your route, then, wherever you stop, the
+    /// elephant's with the valves you left.
+    fn search(
+        &self,
+        current_id: Id,
+        visited: u64,
+        time_left: u32,
+        elephant: bool,
+        memo: &mut Memo,
+    ) -> u32 {
+        let key = (current_id, time_left, visited, elephant);
+        if let Some(&best) = memo.get(&key) {
+            count(&self.stats, SearchStats::memo_hit);
+            return best;
+        }
+        count(&self.stats, |s| s.expand(visited.count_ones() as usize));
+        // Stopping here: start elephant plan, or end it.
+        let mut best = match elephant {
+            false => self.search(AA_ID, visited, 26, true, memo),
+            true => 0,
+        };
+        for (next, flow) in self.flow_map.iter() {
+            let bit = self.valve_bits[next];
+            if visited & bit != 0 {
+                continue;
+            }
+            let Some(time_cost) = self.travel_map.get(&(current_id, *next)).map(|t| t + 1) else {
+                continue;
+            };
+            if time_cost >= time_left {
+                count(&self.stats, SearchStats::prune);
+                continue;
+            }
+            let time_left = time_left - time_cost;
+            let value = flow * time_left;
+            best = best.max(value + self.search(*next, visited | bit, time_left, elephant, memo));
+        }
+        memo.insert(key, best);
+        best
+    }
+}
+
+/// The first part 2: a search over your route and, after each, the elephant's.
+/// Slower, kept as a reference for `part_2`.
+pub fn part_2_elephant(input: &str) -> Result<u32> {
+    let (mut flow_map, tunnel_map) = get_flow_and_tunnel_maps(input)?;
+    let travel_map = get_travel_map(&tunnel_map, &flow_map);
     flow_map.retain(|_, v| *v != 0);
+    if flow_map.len() > 64 {
+        return Err(Error::no_answer(DAY, "more than 64 valves with flow"));
+    }
+    let valve_bits: HashMap<Id, u64> = flow_map
+        .keys()
+        .enumerate()
+        .map(|(i, valve)| (*valve, 1 << i))
+        .collect();
     let dfs = DfsFlowPlanPart2 {
         flow_map: &flow_map,
         travel_map: &travel_map,
-        valve_map: &valve_map,
-        best: Cell::new(0),
+        valve_bits: &valve_bits,
+        stats: Cell::default(),
     };
-    dfs.search(
-        AA_ID,
-        bitarr![0; 16],
-        0,
-        None,
-        26,
-        &mut std::collections::HashSet::new(),
-    );
-    dfs.best.get()
+    let best = dfs.search(AA_ID, 0, 26, false, &mut Memo::new());
+    stats::report("plan with elephant", dfs.stats.get());
+    Ok(best)
+}
+
+pub const STRATEGIES: [Strategy; 2] = [
+    Strategy {
+        name: "valve sets",
+        part: 2,
+        run: |input| part_2(input).map(Into::into),
+    },
+    Strategy {
+        name: "elephant",
+        part: 2,
+        run: |input| part_2_elephant(input).map(Into::into),
+    },
+];
+
+pub fn solve_within(input: &str, part: u8, budget: &Budget) -> Result<Bounded<Answer>> {
+    if part == 1 {
+        Ok(part_1_within(input, budget)?.map(Into::into))
+    } else {
+        Ok(part_2_within(input, budget)?.map(Into::into))
+    }
+}
+
+pub struct Day16;
+impl Solution for Day16 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+    fn part_1(input: &str) -> Result<u32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u32> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day16::*;
-    use std::fs;
+    use crate::manifest::check;
+    #[test]
+    fn budget_runs_out() {
+        let input = std::fs::read_to_string("input/example16").unwrap();
+        let budget = Budget::unlimited().with_node_limit(10);
+        let pressure = part_1_within(&input, &budget).unwrap();
+        assert!(!pressure.complete);
+        assert!(pressure.value < 1651);
+    }
     #[test]
     fn example16_part1() {
-        let input = fs::read_to_string("input/example16").unwrap();
-        assert_eq!(part_1(&input), 1651);
+        check(DAY, 1, "example16");
     }
     #[test]
     fn day16_part1() {
-        let input = fs::read_to_string("input/day16").unwrap();
-        assert_eq!(part_1(&input), 1641);
+        check(DAY, 1, "day16");
     }
     #[test]
     fn example16_part2() {
-        let input = fs::read_to_string("input/example16").unwrap();
-        assert_eq!(part_2(&input), 1707);
+        check(DAY, 2, "example16");
+        let input = std::fs::read_to_string("input/example16").unwrap();
+        assert_eq!(part_2_elephant(&input).unwrap(), 1707);
     }
     #[test]
     fn day16_part2() {
-        let input = fs::read_to_string("input/day16").unwrap();
-        assert_eq!(part_2(&input), 2261);
+        check(DAY, 2, "day16");
+        let input = std::fs::read_to_string("input/day16").unwrap();
+        assert_eq!(part_2_elephant(&input).unwrap(), 2261);
     }
 }
@@ -1,6 +1,14 @@
-use std::{cmp, collections::HashSet, fs};
+use crate::{
+    cycle,
+    error::{Error, Result},
+    parse,
+    render::{self, Canvas, Color, Pixel, Renderer},
+    simulation::Simulation,
+    solution::Solution,
+};
+use std::{cmp, collections::HashSet};
 
-use itertools::Itertools;
+const DAY: u8 = 17;
 
 type Pos = (i64, i64);
 const LEFT_BOUND: i64 = 0;
@@ -142,99 +150,208 @@ enum Dir {
 
 const BLOCK_ORDER: [Type; 5] = [Type::HBar, Type::Cross, Type::RevL, Type::VBar, Type::Box];
 
-fn calc_tower_height(winds: &str, blocks: u64) -> u64 {
-    let mut block_count = 0;
-    let mut highest = 0;
-    let mut type_index = 0;
-    let winds = winds
-        .trim()
-        .chars()
-        .map(|c| match c {
-            '<' => Dir::Left,
-            '>' => Dir::Right,
-            bad => panic!("bad:{bad}"),
+fn parse_winds(input: &str) -> Result<Vec<Dir>> {
+    parse::single(DAY, input, |l| {
+        let mut winds = vec![];
+        while !l.is_done() {
+            winds.push(l.char_map("< or >", |c| match c {
+                '<' => Some(Dir::Left),
+                '>' => Some(Dir::Right),
+                _ => None,
+            })?);
+        }
+        Ok(winds)
+    })
+}
+
+/// Chamber filling up with rocks, one rock dropped per step.
+pub struct Tower {
+    winds: Vec<Dir>,
+    wind_index: usize,
+    type_index: usize,
+    stationary: HashSet<Pos>,
+    highest: i64,
+    dropped: usize,
+}
+
+impl Tower {
+    pub fn new(winds: &str) -> Result<Self> {
+        let winds = parse_winds(winds)?;
+        if winds.is_empty() {
+            return Err(parse::missing(DAY, "", "< or >"));
+        }
+        Ok(Self {
+            winds,
+            wind_index: 0,
+            type_index: 0,
+            stationary: HashSet::new(),
+            highest: 0,
+            dropped: 0,
         })
-        .collect_vec();
-    let mut wind_index = 0;
-    let mut stationary = HashSet::<Pos>::new();
-    while block_count < blocks {
-        let mut new_block = Block::new(BLOCK_ORDER[type_index], highest);
-        type_index = (type_index + 1) % BLOCK_ORDER.len();
-        block_count += 1;
+    }
+    pub fn height(&self) -> i64 {
+        self.highest
+    }
+    fn drop_block(&mut self) {
+        let mut new_block = Block::new(BLOCK_ORDER[self.type_index], self.highest);
+        self.type_index = (self.type_index + 1) % BLOCK_ORDER.len();
         loop {
-            match winds[wind_index] {
-                Dir::Left => new_block.move_left(&stationary),
-                Dir::Right => new_block.move_right(&stationary),
+            match self.winds[self.wind_index] {
+                Dir::Left => new_block.move_left(&self.stationary),
+                Dir::Right => new_block.move_right(&self.stationary),
             }
-            wind_index = (wind_index + 1) % winds.len();
-            if !new_block.drop(&stationary) {
+            self.wind_index = (self.wind_index + 1) % self.winds.len();
+            if !new_block.drop(&self.stationary) {
                 break;
             }
         }
-        highest = cmp::max(highest, new_block.highest());
-        new_block.fill_stationary(&mut stationary);
+        self.highest = cmp::max(self.highest, new_block.highest());
+        new_block.fill_stationary(&mut self.stationary);
+        self.dropped += 1;
+    }
+    /// Next block and wind, plus how far below the top each column's highest rock is.
+    fn fingerprint(&self) -> (usize, usize, [i64; 7]) {
+        let mut skyline = [0; 7];
+        for (x, depth) in (LEFT_BOUND + 1..RIGHT_BOUND).zip(&mut skyline) {
+            *depth = (1..=self.highest)
+                .rev()
+                .find(|&y| self.stationary.contains(&(x, y)))
+                .map_or(self.highest, |y| self.highest - y);
+        }
+        (self.type_index, self.wind_index, skyline)
+    }
+    /// The top `rows` rows of the tower between its walls, and the floor once in view.
+    fn draw(&self, rows: i64) -> Canvas {
+        let top = self.highest.max(rows);
+        let mut canvas = render::canvas(RIGHT_BOUND as usize + 1, rows as usize + 1);
+        let wall = Pixel::new('|', Color::GRAY);
+        for (row, y) in (top - rows + 1..=top).rev().enumerate() {
+            canvas[(LEFT_BOUND as usize, row)] = wall;
+            canvas[(RIGHT_BOUND as usize, row)] = wall;
+            for x in LEFT_BOUND + 1..RIGHT_BOUND {
+                if self.stationary.contains(&(x, y)) {
+                    canvas[(x as usize, row)] = Pixel::new('#', Color::WHITE);
+                }
+            }
+        }
+        for x in LEFT_BOUND..=RIGHT_BOUND {
+            let floor = if top == rows { '-' } else { '~' };
+            canvas[(x as usize, rows as usize)] = Pixel::new(floor, Color::GRAY);
+        }
+        canvas
     }
-    draw(stationary, highest);
-    highest as u64
 }
 
-pub fn part_1(input: &str) -> u64 {
+impl Simulation for Tower {
+    /// The top of the tower, as drawn by `visualize`.
+    type State = Canvas;
+    fn step(&mut self) {
+        self.drop_block();
+    }
+    /// Rocks keep falling forever.
+    fn is_done(&self) -> bool {
+        false
+    }
+    fn snapshot(&self) -> Canvas {
+        self.draw(VIEW_ROWS)
+    }
+    fn ticks(&self) -> usize {
+        self.dropped
+    }
+}
+
+fn calc_tower_height(winds: &str, blocks: usize) -> Result<u64> {
+    let mut tower = Tower::new(winds)?;
+    tower.run(blocks);
+    Ok(tower.highest as u64)
+}
+
+pub fn part_1(input: &str) -> Result<u64> {
     calc_tower_height(input, 2022)
 }
-pub fn part_2(input: &str) -> u64 {
-    calc_tower_height(input, 6000); // 1_000_000_000_000
-    todo!("Hacky solve: Print tower after variable number of blocks to find small base + repeating section.");
+pub fn part_2(input: &str) -> Result<u64> {
+    let tower = Tower::new(input)?;
+    let max_blocks = 20 * tower.winds.len() + 1000;
+    let cycle = cycle::find(
+        tower,
+        Tower::drop_block,
+        Tower::fingerprint,
+        |t| t.highest,
+        max_blocks,
+    )
+    .ok_or_else(|| Error::no_answer(DAY, "the tower never repeats"))?;
+    Ok(cycle.value_at(1_000_000_000_000) as u64)
 }
 
-fn draw(stationary: HashSet<Pos>, highest: i64) {
-    let mut output = String::new();
-    for y in (1..=highest).rev() {
-        let mut line = String::new();
-        line.push('|');
-        for x in 1..8 {
-            if stationary.contains(&(x, y)) {
-                line.push('#');
-            } else {
-                line.push('.');
-            }
-        }
-        line.push('|');
-        output.push_str(&line);
-        output.push('\n');
+/// Rows shown when visualizing, counted down from the top of the tower.
+const VIEW_ROWS: i64 = 40;
+
+/// One frame per block dropped. Both parts drop the same blocks; the first 2022 are shown.
+pub fn visualize(input: &str, _part: u8, renderer: &mut dyn Renderer) -> Result<()> {
+    let mut tower = Tower::new(input)?;
+    while tower.ticks() < 2022 {
+        tower.step();
+        renderer.frame(&tower.snapshot())?;
+    }
+    Ok(())
+}
+
+pub struct Day17;
+impl Solution for Day17 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+    fn part_1(input: &str) -> Result<u64> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u64> {
+        part_2(input)
     }
-    output.push_str("+-------+");
-    let _res = fs::write("output/drawing_day17.txt", output);
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day17::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example17_part1() {
-        assert_eq!(part_1(">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>"), 3068);
+        check(DAY, 1, "example17");
     }
     #[test]
     fn day17_part1() {
-        let input = fs::read_to_string("input/day17").unwrap();
-        assert_eq!(part_1(&input), 3114);
+        check(DAY, 1, "day17");
     }
     #[test]
     fn example17_part2() {
-        assert_eq!(
-            part_2(">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>"),
-            1_514_285_714_288
-        );
+        check(DAY, 2, "example17");
     }
     #[test]
     fn day17_part2() {
-        let input = fs::read_to_string("input/day17").unwrap();
-        let blocks = 1_000_000_000_000u64 - 240;
-        // after 240 blocks (height 365), same pattern repeats every 1740 blocks, with height 2681
-        let div = blocks / 1740;
-        let _rem = dbg!(blocks % 1740);
-        // height of remainder blocks of repeating section = 1434
-        assert_eq!(365 + div * 2681 + 1434, 1540804597682u64);
-        assert_eq!(part_2(&input), 1540804597682u64);
+        check(DAY, 2, "day17");
+    }
+    #[test]
+    fn tower_steps() {
+        let input = std::fs::read_to_string("input/example17").unwrap();
+        let mut tower = Tower::new(&input).unwrap();
+        tower.run(3);
+        let top: Vec<String> = tower
+            .snapshot()
+            .render(|p| p.ch)
+            .lines()
+            .skip(VIEW_ROWS as usize - 6)
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            top,
+            [
+                "|..#....|",
+                "|..#....|",
+                "|####...|",
+                "|..###..|",
+                "|...#...|",
+                "|..####.|",
+                "---------"
+            ]
+        );
+        assert_eq!((tower.ticks(), tower.height()), (3, 6));
     }
 }
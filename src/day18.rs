@@ -1,36 +1,42 @@
+use crate::{error::Result, parse, search, solution::Solution};
 use cgmath::Vector3;
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashSet;
+
+const DAY: u8 = 18;
+
 type V3d = Vector3<i32>;
 type CellSet = HashSet<V3d>;
 type Bounds = (V3d, V3d);
-fn read_cells(s: &str) -> (CellSet, Bounds) {
+fn read_cells(s: &str) -> Result<(CellSet, Bounds)> {
     let mut min_x = i32::MAX;
     let mut min_y = i32::MAX;
     let mut min_z = i32::MAX;
     let mut max_x = i32::MIN;
     let mut max_y = i32::MIN;
     let mut max_z = i32::MIN;
-    let cell_set = s
-        .lines()
-        .map(|l: &str| {
-            let mut coords = l.split(',').map(|n| n.parse::<i32>().unwrap());
-            let (x, y, z) = (
-                coords.next().unwrap(),
-                coords.next().unwrap(),
-                coords.next().unwrap(),
-            );
+    let cell_set: CellSet = parse::lines(DAY, s)
+        .map(|mut l| {
+            let x = l.int()?;
+            l.tag(",")?;
+            let y = l.int()?;
+            l.tag(",")?;
+            let z = l.int()?;
+            l.end()?;
             min_x = min_x.min(x);
             min_y = min_y.min(y);
             min_z = min_z.min(z);
             max_x = max_x.max(x);
             max_y = max_y.max(y);
             max_z = max_z.max(z);
-            V3d::new(x, y, z)
+            Ok(V3d::new(x, y, z))
         })
-        .collect();
+        .collect::<Result<_>>()?;
+    if cell_set.is_empty() {
+        return Err(parse::missing(DAY, s, "a cube"));
+    }
     let min = V3d::new(min_x, min_y, min_z);
     let max = V3d::new(max_x, max_y, max_z);
-    (cell_set, (min, max))
+    Ok((cell_set, (min, max)))
 }
 
 const DIRS: [V3d; 6] = [
@@ -57,22 +63,15 @@ fn get_outside_cells(start: V3d, (min, max): (V3d, V3d), lava_cells: &CellSet) -
             && (min.y - 1..=max.y + 1).contains(&p.y)
             && (min.z - 1..=max.z + 1).contains(&p.z)
     };
-    let mut visited = HashSet::new();
-    let mut to_visit = VecDeque::new();
-    to_visit.push_back(start);
-    visited.insert(start);
-    while let Some(next) = to_visit.pop_front() {
-        for d in DIRS {
-            let neighbor = next + d;
-            if lava_cells.contains(&neighbor) || !in_bounds(neighbor) {
-                continue;
-            }
-            if visited.insert(neighbor) {
-                to_visit.push_back(neighbor);
-            }
-        }
-    }
-    visited
+    let neighbors = |&p: &V3d| {
+        DIRS.iter()
+            .map(move |d| p + d)
+            .filter(|n| !lava_cells.contains(n) && in_bounds(*n))
+    };
+    search::bfs([start], neighbors, |_| false)
+        .dist
+        .into_keys()
+        .collect()
 }
 
 fn surface_area_part2(lava_cells: &CellSet, outside_cells: &CellSet) -> u32 {
@@ -84,38 +83,55 @@ fn surface_area_part2(lava_cells: &CellSet, outside_cells: &CellSet) -> u32 {
     lava_cells.iter().map(cell_surface).sum()
 }
 
-pub fn part_1(input: &str) -> u32 {
-    let (cell_set, _) = read_cells(input);
-    surface_area(&cell_set)
+pub fn part_1(input: &str) -> Result<u32> {
+    let (cell_set, _) = read_cells(input)?;
+    Ok(surface_area(&cell_set))
 }
-pub fn part_2(input: &str) -> u32 {
-    let (lava_cells, bounds) = read_cells(input);
+pub fn part_2(input: &str) -> Result<u32> {
+    let (lava_cells, bounds) = read_cells(input)?;
     let outside_cells = get_outside_cells(bounds.0, bounds, &lava_cells);
-    surface_area_part2(&lava_cells, &outside_cells)
+    Ok(surface_area_part2(&lava_cells, &outside_cells))
+}
+
+pub struct Day18;
+impl Solution for Day18 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+    fn part_1(input: &str) -> Result<u32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<u32> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day18::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example18_part1() {
-        let input = fs::read_to_string("input/example18").unwrap();
-        assert_eq!(part_1(&input), 64);
+        check(DAY, 1, "example18");
     }
     #[test]
     fn day18_part1() {
-        let input = fs::read_to_string("input/day18").unwrap();
-        assert_eq!(part_1(&input), 4628);
+        check(DAY, 1, "day18");
     }
     #[test]
     fn example18_part2() {
-        let input = fs::read_to_string("input/example18").unwrap();
-        assert_eq!(part_2(&input), 58);
+        check(DAY, 2, "example18");
     }
     #[test]
     fn day18_part2() {
-        let input = fs::read_to_string("input/day18").unwrap();
-        assert_eq!(part_2(&input), 2582);
+        check(DAY, 2, "day18");
+    }
+    #[test]
+    fn no_cubes() {
+        let err = part_2("").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "day 18, line 1, column 1: expected a cube, found nothing"
+        );
+        assert!(part_1("").is_err());
     }
 }
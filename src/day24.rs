@@ -1,4 +1,18 @@
-use std::collections::{HashMap, HashSet};
+use crate::{
+    budget::{Bounded, Budget},
+    cycle,
+    error::{Error, Result},
+    grid::Grid,
+    parse,
+    render::{self, Canvas, Color, Pixel, Renderer},
+    search,
+    solution::{Answer, Solution},
+    stats,
+};
+use std::collections::HashMap;
+
+const DAY: u8 = 24;
+
 type V2d = cgmath::Vector2<i32>;
 
 #[derive(Clone, Copy)]
@@ -22,14 +36,7 @@ struct Wind {
 }
 
 impl Wind {
-    fn new(start: V2d, dir_char: char) -> Self {
-        let dir = match dir_char {
-            '^' => Dir::N,
-            'v' => Dir::S,
-            '>' => Dir::E,
-            '<' => Dir::W,
-            _ => panic!(),
-        };
+    fn new(start: V2d, dir: Dir) -> Self {
         Self { start, dir }
     }
     fn pos_at_t(&self, t: i32, bounds: &Bounds) -> V2d {
@@ -67,169 +74,277 @@ struct Bounds {
 }
 
 type WindsByCoordinate = HashMap<i32, Vec<Wind>>;
-fn parse_winds_and_bounds(s: &str) -> (WindsByCoordinate, WindsByCoordinate, Bounds) {
+fn parse_winds_and_bounds(s: &str) -> Result<(WindsByCoordinate, WindsByCoordinate, Bounds)> {
+    let grid = Grid::parse(DAY, s, "'#', '.', '^', 'v', '>' or '<'", |c| match c {
+        '#' | '.' => Some(None),
+        '^' => Some(Some(Dir::N)),
+        'v' => Some(Some(Dir::S)),
+        '>' => Some(Some(Dir::E)),
+        '<' => Some(Some(Dir::W)),
+        _ => None,
+    })?;
+    let (width, height) = (grid.width() as i32, grid.height() as i32);
+    if width < 3 || height < 3 {
+        return Err(parse::missing(DAY, s, "a basin at least 3x3 with walls"));
+    }
     let mut winds_by_x = WindsByCoordinate::new();
     let mut winds_by_y = WindsByCoordinate::new();
-    let height = s.lines().count();
-    for (y, line) in s.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let x = x as i32;
-            let y = height as i32 - y as i32 - 1;
-            let pos = V2d::new(x, y);
-            match c {
-                w @ '^' | w @ 'v' | w @ '>' | w @ '<' => {
-                    let new_wind = Wind::new(pos, w);
-                    winds_by_x.entry(x).or_default().push(new_wind.clone());
-                    winds_by_y.entry(y).or_default().push(new_wind);
-                }
-                _ => (),
-            }
+    for ((x, y), dir) in grid.iter() {
+        if let Some(dir) = *dir {
+            let pos = V2d::new(x as i32, height - y as i32 - 1);
+            let new_wind = Wind::new(pos, dir);
+            winds_by_x.entry(pos.x).or_default().push(new_wind.clone());
+            winds_by_y.entry(pos.y).or_default().push(new_wind);
         }
     }
-    let width = s.lines().next().unwrap().chars().count();
     let bounds = Bounds {
-        x_bounds: (1, width as i32 - 2),
-        y_bounds: (1, height as i32 - 2),
+        x_bounds: (1, width - 2),
+        y_bounds: (1, height - 2),
     };
-    (winds_by_x, winds_by_y, bounds)
+    Ok((winds_by_x, winds_by_y, bounds))
 }
 
 struct Searcher {
-    start: V2d,
-    goal: V2d,
     winds_by_x: WindsByCoordinate,
     winds_by_y: WindsByCoordinate,
     bounds: Bounds,
+    /// The winds are back where they started after this many minutes.
+    period: i32,
 }
 
 impl Searcher {
-    fn recursive_search(
+    fn new(input: &str) -> Result<Self> {
+        let (winds_by_x, winds_by_y, bounds) = parse_winds_and_bounds(input)?;
+        let mut searcher = Self {
+            winds_by_x,
+            winds_by_y,
+            bounds,
+            period: 1,
+        };
+        searcher.period = searcher.wind_period();
+        Ok(searcher)
+    }
+    fn wind_period(&self) -> i32 {
+        let width = self.bounds.x_bounds.1 - self.bounds.x_bounds.0 + 1;
+        let height = self.bounds.y_bounds.1 - self.bounds.y_bounds.0 + 1;
+        let winds_at = |t: &i32| {
+            let mut winds: Vec<_> = self
+                .winds_by_x
+                .values()
+                .flatten()
+                .map(|w| w.pos_at_t(*t, &self.bounds))
+                .collect();
+            winds.sort_by_key(|p| (p.x, p.y));
+            winds
+        };
+        // The winds always line up again after width * height minutes.
+        let max = (width * height) as usize;
+        cycle::find(0, |t| *t += 1, winds_at, |_| 0, max).map_or(max as i32, |c| c.period as i32)
+    }
+    fn start(&self) -> V2d {
+        V2d::new(self.bounds.x_bounds.0, self.bounds.y_bounds.1 + 1)
+    }
+    fn goal(&self) -> V2d {
+        V2d::new(self.bounds.x_bounds.1, 0)
+    }
+    fn no_winds_at(&self, pos: V2d, time: i32) -> bool {
+        let no_winds_here = |w: Option<&Vec<Wind>>| {
+            w.into_iter()
+                .flatten()
+                .all(|w| w.pos_at_t(time, &self.bounds) != pos)
+        };
+        no_winds_here(self.winds_by_x.get(&pos.x)) && no_winds_here(self.winds_by_y.get(&pos.y))
+    }
+    /// Breadth first search from `start` (left at `time`) towards `goal`.
+    fn explore(
         &self,
-        pos: V2d,
+        start: V2d,
+        goal: V2d,
         time: i32,
-        visited: &mut HashSet<(V2d, i32)>,
-        best: &mut i32,
-        recursions: u32,
-    ) {
-        if recursions > 400 || !visited.insert((pos, time)) {
-            return;
+        budget: &Budget,
+    ) -> search::Search<(V2d, i32), usize> {
+        let x_range = self.bounds.x_bounds.0..=self.bounds.x_bounds.1;
+        let y_range = self.bounds.y_bounds.0..=self.bounds.y_bounds.1;
+        let in_bounds = |v: &V2d| x_range.contains(&v.x) && y_range.contains(&v.y);
+        // Winds repeat, so only the time within the period matters.
+        let next_moves = |&(pos, phase): &(V2d, i32)| {
+            let t = phase + 1;
+            [N, S, W, E, WAIT]
+                .into_iter()
+                .map(move |dir| pos + dir)
+                .filter(move |next| {
+                    (in_bounds(next) || *next == start || *next == goal)
+                        && self.no_winds_at(*next, t)
+                })
+                .map(move |next| (next, t % self.period))
+        };
+        let starts = [(start, time % self.period)];
+        let search = search::bfs_within(starts, next_moves, |(pos, _)| *pos == goal, budget);
+        stats::report("route", search.stats);
+        search
+    }
+    /// Positions minute by minute on a fastest way from `start` (left at `time`) to `goal`.
+    fn route(&self, start: V2d, goal: V2d, time: i32) -> Result<Vec<V2d>> {
+        let search = self.explore(start, goal, time, &Budget::unlimited());
+        search
+            .goal
+            .and_then(|g| search.path(&g))
+            .map(|path| path.into_iter().map(|(pos, _)| pos).collect())
+            .ok_or_else(|| Error::no_answer(DAY, "no way through the blizzards"))
+    }
+    /// Earliest time `goal` can be reached when leaving `start` at `time`. If `budget`
+    /// runs out first, a time it can't be reached before.
+    fn search(&self, start: V2d, goal: V2d, time: i32, budget: &Budget) -> Result<Bounded<i32>> {
+        let search = self.explore(start, goal, time, budget);
+        match search.goal_distance() {
+            Some(minutes) => Ok(Bounded::complete(time + minutes as i32)),
+            None if budget.is_exhausted() => {
+                Ok(budget.finish(time + search.stats.max_depth as i32))
+            }
+            None => Err(Error::no_answer(DAY, "no way through the blizzards")),
+        }
+    }
+    /// The basin at `time`, with the expedition at `pos`. Overlapping winds show as a count.
+    fn draw(&self, pos: V2d, time: i32) -> Canvas {
+        let width = self.bounds.x_bounds.1 + 2;
+        let height = self.bounds.y_bounds.1 + 2;
+        let cell = |p: V2d| (p.x as usize, (height - 1 - p.y) as usize);
+        let mut canvas = render::canvas(width as usize, height as usize);
+        for x in 0..width {
+            for y in [0, height - 1] {
+                canvas[cell(V2d::new(x, y))] = Pixel::new('#', Color::GRAY);
+            }
         }
-        if pos == self.goal {
-            if time < *best {
-                *best = time;
+        for y in 0..height {
+            for x in [0, width - 1] {
+                canvas[cell(V2d::new(x, y))] = Pixel::new('#', Color::GRAY);
             }
-            return;
         }
+        for p in [self.start(), self.goal()] {
+            canvas[cell(p)] = Pixel::EMPTY;
+        }
+        for w in self.winds_by_x.values().flatten() {
+            let c = &mut canvas[cell(w.pos_at_t(time, &self.bounds))];
+            let ch = match (c.ch, w.dir) {
+                ('.', Dir::N) => '^',
+                ('.', Dir::S) => 'v',
+                ('.', Dir::E) => '>',
+                ('.', Dir::W) => '<',
+                (n @ '2'..='8', _) => (n as u8 + 1) as char,
+                _ => '2',
+            };
+            *c = Pixel::new(ch, Color::BLUE);
+        }
+        canvas[cell(pos)] = Pixel::new('E', Color::YELLOW);
+        canvas
+    }
+}
 
-        let x_range = self.bounds.x_bounds.0..=self.bounds.x_bounds.1;
-        let y_range = self.bounds.y_bounds.0..=self.bounds.y_bounds.1;
-        let in_bounds = |v: &V2d| x_range.contains(&v.x) && y_range.contains(&v.y);
+pub fn part_1(input: &str) -> Result<i32> {
+    part_1_within(input, &Budget::unlimited()).map(|b| b.value)
+}
+
+pub fn part_2(input: &str) -> Result<i32> {
+    part_2_within(input, &Budget::unlimited()).map(|b| b.value)
+}
+
+pub fn part_1_within(input: &str, budget: &Budget) -> Result<Bounded<i32>> {
+    let searcher = Searcher::new(input)?;
+    searcher.search(searcher.start(), searcher.goal(), 0, budget)
+}
 
-        let next_moves = [N, S, W, E, WAIT]
-            .iter()
-            .map(|dir| pos + dir)
-            .filter(move |next| {
-                if !(in_bounds(next) || *next == self.start || *next == self.goal) {
-                    return false;
-                }
-                let x_winds = self.winds_by_x.get(&next.x);
-                let y_winds = self.winds_by_y.get(&next.y);
-                let no_winds_here = |w: &[Wind]| {
-                    w.iter()
-                        .all(|w| w.pos_at_t(time + 1, &self.bounds) != *next)
-                };
-                match (x_winds, y_winds) {
-                    (None, None) => true,
-                    (None, Some(w)) => no_winds_here(w),
-                    (Some(w), None) => no_winds_here(w),
-                    (Some(wx), Some(wy)) => no_winds_here(wx) && no_winds_here(wy),
-                }
-            });
-        for n in next_moves {
-            self.recursive_search(n, time + 1, visited, best, recursions + 1);
+/// Stops at the first leg the budget runs out on.
+pub fn part_2_within(input: &str, budget: &Budget) -> Result<Bounded<i32>> {
+    let searcher = Searcher::new(input)?;
+    let (start, goal) = (searcher.start(), searcher.goal());
+    let mut time = Bounded::complete(0);
+    for (from, to) in [(start, goal), (goal, start), (start, goal)] {
+        time = searcher.search(from, to, time.value, budget)?;
+        if !time.complete {
+            break;
         }
     }
+    Ok(time)
 }
 
-pub fn part_1(input: &str) -> i32 {
-    let (winds_by_x, winds_by_y, bounds) = parse_winds_and_bounds(input);
-    let goal = V2d::new(bounds.x_bounds.1, 0);
-    let start = V2d::new(bounds.x_bounds.0, bounds.y_bounds.1 + 1);
-    let mut best = i32::MAX;
-    let mut visited = HashSet::new();
-    let searcher = Searcher {
-        start,
-        goal,
-        winds_by_x,
-        winds_by_y,
-        bounds,
-    };
-    searcher.recursive_search(start, 0, &mut visited, &mut best, 0);
-    best
+pub fn solve_within(input: &str, part: u8, budget: &Budget) -> Result<Bounded<Answer>> {
+    if part == 1 {
+        Ok(part_1_within(input, budget)?.map(Into::into))
+    } else {
+        Ok(part_2_within(input, budget)?.map(Into::into))
+    }
 }
 
-pub fn part_2(input: &str) -> i32 {
-    let (winds_by_x, winds_by_y, bounds) = parse_winds_and_bounds(input);
-    let goal = V2d::new(bounds.x_bounds.1, 0);
-    let start = V2d::new(bounds.x_bounds.0, bounds.y_bounds.1 + 1);
-    let mut best = i32::MAX;
-    let mut visited = HashSet::new();
-    let mut searcher = Searcher {
-        start,
-        goal,
-        winds_by_x,
-        winds_by_y,
-        bounds,
+/// One frame per minute along a fastest route; part 2 goes there, back and there again.
+pub fn visualize(input: &str, part: u8, renderer: &mut dyn Renderer) -> Result<()> {
+    let searcher = Searcher::new(input)?;
+    let (start, goal) = (searcher.start(), searcher.goal());
+    let legs = if part == 1 {
+        vec![(start, goal)]
+    } else {
+        vec![(start, goal), (goal, start), (start, goal)]
     };
-    searcher.recursive_search(start, 0, &mut visited, &mut best, 0);
-    let time2 = best;
-    best = i32::MAX;
-    visited.clear();
-    searcher.start = goal;
-    searcher.goal = start;
-    searcher.recursive_search(goal, time2, &mut visited, &mut best, 0);
-    let time3 = best;
-    best = i32::MAX;
-    visited.clear();
-    searcher.start = start;
-    searcher.goal = goal;
-    searcher.recursive_search(start, time3, &mut visited, &mut best, 0);
-    best
+    let mut time = 0;
+    renderer.frame(&searcher.draw(start, time))?;
+    for (from, to) in legs {
+        let route = searcher.route(from, to, time)?;
+        for pos in route.into_iter().skip(1) {
+            time += 1;
+            renderer.frame(&searcher.draw(pos, time))?;
+        }
+    }
+    Ok(())
+}
+
+pub struct Day24;
+impl Solution for Day24 {
+    type Answer1 = i32;
+    type Answer2 = i32;
+    fn part_1(input: &str) -> Result<i32> {
+        part_1(input)
+    }
+    fn part_2(input: &str) -> Result<i32> {
+        part_2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day24::*;
-    use std::fs;
+    use crate::manifest::check;
     #[test]
     fn winds() {
-        let w = Wind::new(V2d::new(1, 1), '^');
-        dbg!(w.pos_at_t(
-            9,
-            &Bounds {
-                x_bounds: (1, 4),
-                y_bounds: (1, 4)
-            }
-        ));
+        let w = Wind::new(V2d::new(1, 1), Dir::N);
+        let bounds = Bounds {
+            x_bounds: (1, 4),
+            y_bounds: (1, 4),
+        };
+        assert_eq!(w.pos_at_t(9, &bounds), V2d::new(1, 2));
+    }
+    #[test]
+    fn budget_runs_out() {
+        let input = std::fs::read_to_string("input/example24").unwrap();
+        let budget = Budget::unlimited().with_node_limit(20);
+        let time = part_2_within(&input, &budget).unwrap();
+        assert!(!time.complete);
+        assert!((1..18).contains(&time.value));
+        let time = part_2_within(&input, &Budget::unlimited()).unwrap();
+        assert_eq!(time, Bounded::complete(54));
     }
     #[test]
     fn example24_part1() {
-        let input = fs::read_to_string("input/example24").unwrap();
-        assert_eq!(part_1(&input), 18);
+        check(DAY, 1, "example24");
     }
     #[test]
     fn day24_part1() {
-        let input = fs::read_to_string("input/day24").unwrap();
-        assert_eq!(part_1(&input), 264);
+        check(DAY, 1, "day24");
     }
     #[test]
     fn example24_part2() {
-        let input = fs::read_to_string("input/example24").unwrap();
-        assert_eq!(part_2(&input), 54);
+        check(DAY, 2, "example24");
     }
     #[test]
     fn day24_part2() {
-        let input = fs::read_to_string("input/day24").unwrap();
-        assert_eq!(part_2(&input), 789);
+        check(DAY, 2, "day24");
     }
 }
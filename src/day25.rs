@@ -1,24 +1,37 @@
-fn snafu_to_decimal(snafu: &str) -> i64 {
-    snafu
-        .bytes()
-        .rev()
-        .enumerate()
-        .map(|(i, c)| match c {
-            b'0' => 0,
-            b'1' => 1,
-            b'2' => 2,
-            b'-' => -1,
-            b'=' => -2,
-            _ => panic!(),
-        } * 5i64.pow(i as u32))
-        .sum()
+use crate::{
+    error::{Error, Result},
+    parse::{self, Line},
+    solution::{Answer, Solution},
+};
+use std::io::BufRead;
+
+const DAY: u8 = 25;
+
+fn snafu_to_decimal(mut snafu: Line) -> Result<i64> {
+    let mut n = 0;
+    while !snafu.is_done() {
+        n = 5 * n
+            + snafu.char_map("a SNAFU digit", |c| match c {
+                '0' => Some(0),
+                '1' => Some(1),
+                '2' => Some(2),
+                '-' => Some(-1),
+                '=' => Some(-2),
+                _ => None,
+            })?;
+    }
+    Ok(n)
 }
 const SNAFU: [(i8, u8); 5] = [(-2, b'='), (-1, b'-'), (0, b'0'), (1, b'1'), (2, b'2')];
-fn decimal_to_snafu(i: u64) -> String {
+pub(crate) fn decimal_to_snafu(i: u64) -> String {
+    if i == 0 {
+        return "0".to_string();
+    }
+    // The fewest digits whose largest number, all 2s, reaches `i`.
     let mut current = 0;
     let mut symbols = 0;
     for exp in 0u32.. {
-        if current > i {
+        if current >= i {
             break;
         }
         current += 2 * 5u64.pow(exp);
@@ -40,29 +53,75 @@ fn decimal_to_snafu(i: u64) -> String {
     unsafe { String::from_utf8_unchecked(res) }
 }
 
-pub fn part_1(input: &str) -> String {
-    let sum: i64 = input.lines().map(snafu_to_decimal).sum();
-    decimal_to_snafu(sum as u64)
+pub fn part_1_read(input: &mut dyn BufRead) -> Result<String> {
+    let sum: i64 = parse::read_lines(DAY, input, snafu_to_decimal).sum::<Result<_>>()?;
+    let sum = u64::try_from(sum)
+        .map_err(|_| Error::no_answer(DAY, format!("the sum {sum} is negative")))?;
+    Ok(decimal_to_snafu(sum))
+}
+
+pub fn part_1(input: &str) -> Result<String> {
+    part_1_read(&mut input.as_bytes())
+}
+
+pub struct Day25;
+impl Solution for Day25 {
+    type Answer1 = String;
+    type Answer2 = Answer;
+    fn part_1(input: &str) -> Result<String> {
+        part_1(input)
+    }
+    fn part_2(_input: &str) -> Result<Answer> {
+        Ok(Answer::None)
+    }
+    fn part_1_read(input: &mut dyn BufRead) -> Result<String> {
+        part_1_read(input)
+    }
+    fn part_2_read(_input: &mut dyn BufRead) -> Result<Answer> {
+        Ok(Answer::None)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day25::*;
-    use std::fs;
+    use crate::manifest::{check, check_read};
     #[test]
     fn sna_to_dec() {
-        assert_eq!(snafu_to_decimal("1=-0-2"), 1747);
+        assert_eq!(snafu_to_decimal(Line::new(DAY, 1, "1=-0-2")).unwrap(), 1747);
     }
     #[test]
     fn dec_to_sna() {
         for _ in 0..1_000_000 {
             assert_eq!(decimal_to_snafu(1747), "1=-0-2");
         }
+        assert_eq!(decimal_to_snafu(0), "0");
+        assert_eq!(decimal_to_snafu(2), "2");
+        assert_eq!(decimal_to_snafu(3), "1=");
+        assert_eq!(decimal_to_snafu(12), "22");
+        assert_eq!(decimal_to_snafu(13), "1==");
+        for n in 0..1000 {
+            let snafu = decimal_to_snafu(n);
+            assert_eq!(
+                snafu_to_decimal(Line::new(DAY, 1, &snafu)).unwrap(),
+                n as i64
+            );
+            assert!(n == 0 || !snafu.starts_with('0'), "{n}: {snafu}");
+        }
+    }
+    #[test]
+    fn negative_sum() {
+        assert_eq!(part_1("1=\n-\n").unwrap(), "2");
+        let err = part_1("-\n").unwrap_err().to_string();
+        assert_eq!(err, "day 25: no answer, the sum -1 is negative");
     }
 
     #[test]
     fn day25_part1() {
-        let input = fs::read_to_string("input/day25").unwrap();
-        assert_eq!(part_1(&input), "2-10==12-122-=1-1-22");
+        check(DAY, 1, "day25");
+    }
+    #[test]
+    fn day25_read() {
+        check_read(DAY, 1, "day25");
     }
 }
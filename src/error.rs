@@ -0,0 +1,75 @@
+use std::fmt;
+
+/// Where and why an input line failed to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    /// 1-based line number in the input.
+    pub line: usize,
+    /// 1-based column, counted in chars.
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    /// The input parsed, but has no answer (e.g. no start-of-packet marker in day 6).
+    NoAnswer {
+        day: u8,
+        reason: String,
+    },
+    /// Reading input or writing output failed, e.g. visualization frames.
+    Io(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub(crate) fn no_answer(day: u8, reason: impl Into<String>) -> Self {
+        Error::NoAnswer {
+            day,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e.to_string())
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {:02}, line {}, column {}: expected {}, found ",
+            self.day, self.line, self.column, self.expected
+        )?;
+        if self.found.is_empty() {
+            write!(f, "nothing")
+        } else {
+            write!(f, "{:?}", self.found)
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(e) => e.fmt(f),
+            Error::NoAnswer { day, reason } => write!(f, "day {day:02}: no answer, {reason}"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
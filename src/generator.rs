@@ -0,0 +1,772 @@
+use crate::day10::FONT;
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Write,
+    ops::RangeInclusive,
+};
+
+/// Small seedable random number generator (SplitMix64), so generated inputs can be
+/// reproduced from their seed.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    /// Uniform in `0..n`, `n` must not be 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let span = (range.end() - range.start()) as u64 + 1;
+        range.start() + ((self.next_u64() as u128 * span as u128) >> 64) as i64
+    }
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+    /// `len` random lowercase letters.
+    fn word(&mut self, len: usize) -> String {
+        (0..len)
+            .map(|_| (b'a' + self.below(26) as u8) as char)
+            .collect()
+    }
+}
+
+/// Makes a valid input for one day. `size` scales it; what it counts depends on
+/// the day, see each generator.
+pub type Generator = fn(&mut Rng, usize) -> String;
+
+pub const GENERATORS: [Generator; 25] = [
+    calories,
+    strategy_guide,
+    rucksacks,
+    section_pairs,
+    crate_stacks,
+    datastream,
+    terminal,
+    tree_heights,
+    rope_moves,
+    program,
+    monkeys,
+    heightmap,
+    packets,
+    rock_paths,
+    sensors,
+    valves,
+    jets,
+    cubes,
+    blueprints,
+    encrypted_file,
+    monkey_math,
+    monkey_map,
+    elves,
+    basin,
+    snafu_numbers,
+];
+
+/// Input for `day` of the given size from `seed`, `None` if there's no such day.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<String> {
+    let generator = GENERATORS.get(day.checked_sub(1)? as usize)?;
+    Some(generator(&mut Rng::new(seed), size))
+}
+
+/// `size` elves, at least three.
+fn calories(rng: &mut Rng, size: usize) -> String {
+    let elves: Vec<String> = (0..size.max(3))
+        .map(|_| {
+            let items = rng.range(1..=8);
+            (0..items).fold(String::new(), |mut s, _| {
+                writeln!(s, "{}", rng.range(1000..=60000)).unwrap();
+                s
+            })
+        })
+        .collect();
+    elves.join("\n")
+}
+
+/// `size` rounds.
+fn strategy_guide(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size {
+        writeln!(
+            s,
+            "{} {}",
+            rng.pick(&['A', 'B', 'C']),
+            rng.pick(&['X', 'Y', 'Z'])
+        )
+        .unwrap();
+    }
+    s
+}
+
+/// `size` groups of three rucksacks. Each rucksack has exactly one item in both
+/// compartments and each group exactly one item in all three.
+fn rucksacks(rng: &mut Rng, size: usize) -> String {
+    let mut letters: Vec<u8> = (b'a'..=b'z').chain(b'A'..=b'Z').collect();
+    let mut s = String::new();
+    for _ in 0..size.max(1) {
+        rng.shuffle(&mut letters);
+        let badge = letters[0];
+        // Every rucksack draws from its own 17 letters, plus the badge.
+        for pool in letters[1..].chunks(17) {
+            let (shared, left, right) = (pool[0], &pool[1..9], &pool[9..]);
+            let half = rng.range(4..=16) as usize;
+            let mut first = vec![shared];
+            first.extend((1..half).map(|_| *rng.pick(left)));
+            let mut second = vec![shared, badge];
+            second.extend((2..half).map(|_| *rng.pick(right)));
+            rng.shuffle(&mut first);
+            rng.shuffle(&mut second);
+            first.extend(second);
+            s += std::str::from_utf8(&first).unwrap();
+            s.push('\n');
+        }
+    }
+    s
+}
+
+/// `size` pairs of ranges.
+fn section_pairs(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size {
+        let mut range = || {
+            let start = rng.range(1..=99);
+            (start, rng.range(start..=99))
+        };
+        let (a, b) = (range(), range());
+        writeln!(s, "{}-{},{}-{}", a.0, a.1, b.0, b.1).unwrap();
+    }
+    s
+}
+
+/// Nine stacks and `size` moves, none of which empties a stack.
+fn crate_stacks(rng: &mut Rng, size: usize) -> String {
+    let mut heights: Vec<usize> = (0..9).map(|_| rng.range(2..=8) as usize).collect();
+    let mut s = String::new();
+    for &height in &heights {
+        let crates: Vec<String> = (0..height)
+            .map(|_| ((b'A' + rng.below(26) as u8) as char).to_string())
+            .collect();
+        writeln!(s, "{}", crates.join(" ")).unwrap();
+    }
+    s.push('\n');
+    for _ in 0..size {
+        let from = loop {
+            let from = rng.below(heights.len());
+            if heights[from] > 1 {
+                break from;
+            }
+        };
+        let to = (from + 1 + rng.below(heights.len() - 1)) % heights.len();
+        let count = rng.range(1..=heights[from] as i64 - 1) as usize;
+        heights[from] -= count;
+        heights[to] += count;
+        writeln!(s, "move {count} from {} to {}", from + 1, to + 1).unwrap();
+    }
+    s
+}
+
+/// About `size` chars, with a start-of-message marker somewhere.
+fn datastream(rng: &mut Rng, size: usize) -> String {
+    // Three letters can't make a marker, so the one placed is the first.
+    let mut s: String = (0..rng.below(size + 1))
+        .map(|_| *rng.pick(&['a', 'b', 'c']))
+        .collect();
+    let mut letters: Vec<char> = ('a'..='z').collect();
+    rng.shuffle(&mut letters);
+    s.extend(&letters[..14]);
+    s += &rng.word(size / 2);
+    s.push('\n');
+    s
+}
+
+/// About `size` files in a random tree of directories, explored depth first.
+fn terminal(rng: &mut Rng, size: usize) -> String {
+    struct Dir {
+        name: String,
+        entries: Vec<String>,
+        subdirs: Vec<usize>,
+    }
+    let mut dirs = vec![Dir {
+        name: "/".to_string(),
+        entries: Vec::new(),
+        subdirs: Vec::new(),
+    }];
+    let mut names: Vec<HashSet<String>> = vec![HashSet::new()];
+    let unique_name = |rng: &mut Rng, names: &mut HashSet<String>| loop {
+        let len = rng.range(1..=8) as usize;
+        let name = rng.word(len);
+        if names.insert(name.clone()) {
+            break name;
+        }
+    };
+    for _ in 0..size / 4 {
+        let parent = rng.below(dirs.len());
+        let name = unique_name(rng, &mut names[parent]);
+        dirs[parent].entries.push(format!("dir {name}"));
+        let child = dirs.len();
+        dirs[parent].subdirs.push(child);
+        dirs.push(Dir {
+            name,
+            entries: Vec::new(),
+            subdirs: Vec::new(),
+        });
+        names.push(HashSet::new());
+    }
+    for _ in 0..size.max(1) {
+        let dir = rng.below(dirs.len());
+        let mut name = unique_name(rng, &mut names[dir]);
+        if rng.chance(0.5) {
+            name = format!("{name}.{}", rng.word(3));
+        }
+        let file = format!("{} {name}", rng.range(1000..=300_000));
+        dirs[dir].entries.push(file);
+    }
+    fn explore(dirs: &mut [Dir], i: usize, rng: &mut Rng, s: &mut String) {
+        writeln!(s, "$ cd {}", dirs[i].name).unwrap();
+        s.push_str("$ ls\n");
+        rng.shuffle(&mut dirs[i].entries);
+        for entry in &dirs[i].entries {
+            writeln!(s, "{entry}").unwrap();
+        }
+        for subdir in dirs[i].subdirs.clone() {
+            explore(dirs, subdir, rng, s);
+            s.push_str("$ cd ..\n");
+        }
+    }
+    let mut s = String::new();
+    explore(&mut dirs, 0, rng, &mut s);
+    s
+}
+
+/// A `size` by `size` grid.
+fn tree_heights(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size.max(1) {
+        for _ in 0..size.max(1) {
+            s.push((b'0' + rng.below(10) as u8) as char);
+        }
+        s.push('\n');
+    }
+    s
+}
+
+/// `size` moves.
+fn rope_moves(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size {
+        writeln!(
+            s,
+            "{} {}",
+            rng.pick(&['U', 'D', 'L', 'R']),
+            rng.range(1..=20)
+        )
+        .unwrap();
+    }
+    s
+}
+
+/// Draws eight random letters, so part 2 can read them; `size` is ignored. The
+/// register is set once per two cycles, to light or not the two pixels drawn then.
+fn program(rng: &mut Rng, _size: usize) -> String {
+    // The first two pixels are lit by the register starting at 1.
+    let first: Vec<_> = FONT
+        .iter()
+        .filter(|(_, g)| g[0].starts_with("##"))
+        .collect();
+    let mut letters = vec![rng.pick(&first).1];
+    letters.extend((1..8).map(|_| rng.pick(&FONT).1));
+    let (mut s, mut x) = (String::new(), 1);
+    for row in 0..6 {
+        let pixels: Vec<bool> = letters
+            .iter()
+            .flat_map(|g| g[row].chars().chain(['.']))
+            .map(|c| c == '#')
+            .collect();
+        for (i, pair) in pixels.chunks(2).enumerate() {
+            let col = 2 * i as i64;
+            let next = match (pair[0], pair[1]) {
+                (true, true) => col + rng.range(0..=1),
+                (true, false) => col - 1,
+                (false, true) => col + 2,
+                (false, false) => *rng.pick(&[col - 3, col + 4]),
+            };
+            if (row, i) == (0, 0) {
+                continue;
+            }
+            // Set during the pair before.
+            match next - x {
+                0 => s += "noop\nnoop\n",
+                n => writeln!(s, "addx {n}").unwrap(),
+            }
+            x = next;
+        }
+    }
+    s
+}
+
+/// `size` monkeys, between 2 and 8. Their worry levels grow slowly enough for
+/// part 1 not to overflow.
+fn monkeys(rng: &mut Rng, size: usize) -> String {
+    let count = size.clamp(2, 8);
+    let mut primes = [2, 3, 5, 7, 11, 13, 17, 19];
+    rng.shuffle(&mut primes);
+    let mut blocks = Vec::new();
+    for (i, prime) in primes.iter().take(count).enumerate() {
+        let items: Vec<String> = (0..rng.range(1..=6))
+            .map(|_| rng.range(50..=99).to_string())
+            .collect();
+        let op = if rng.chance(0.5) {
+            format!("* {}", rng.range(2..=3))
+        } else {
+            format!("+ {}", rng.range(1..=8))
+        };
+        let mut other = || (i + 1 + rng.below(count - 1)) % count;
+        let (if_true, if_false) = (other(), other());
+        blocks.push(format!(
+            "Monkey {i}:\n  Starting items: {}\n  Operation: new = old {op}\n  \
+             Test: divisible by {prime}\n    If true: throw to monkey {if_true}\n    \
+             If false: throw to monkey {if_false}\n",
+            items.join(", ")
+        ));
+    }
+    blocks.join("\n")
+}
+
+/// At least 26 wide and `size / 2` high. One row climbs steadily from S to E, so
+/// there's always a path.
+fn heightmap(rng: &mut Rng, size: usize) -> String {
+    let (width, height) = (size.max(26), (size / 2).max(3));
+    let path_row = rng.below(height);
+    let mut s = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            let base = (x * 25 / (width - 1)) as u8;
+            let c = match (x, y == path_row) {
+                (0, true) => 'S',
+                (x, true) if x == width - 1 => 'E',
+                (_, true) => (b'a' + base) as char,
+                _ => (b'a' + base.saturating_sub(rng.below(3) as u8)) as char,
+            };
+            s.push(c);
+        }
+        s.push('\n');
+    }
+    s
+}
+
+/// `size` pairs. The packets of a pair start with different numbers, so they're
+/// never equal.
+fn packets(rng: &mut Rng, size: usize) -> String {
+    fn list(rng: &mut Rng, depth: usize) -> String {
+        let items: Vec<String> = (0..rng.range(0..=4))
+            .map(|_| {
+                if depth < 4 && rng.chance(0.3) {
+                    list(rng, depth + 1)
+                } else {
+                    rng.range(0..=10).to_string()
+                }
+            })
+            .collect();
+        format!("[{}]", items.join(","))
+    }
+    // Starts with `first`, either plainly or wrapped in lists.
+    fn packet(rng: &mut Rng, first: i64) -> String {
+        let mut head = first.to_string();
+        while rng.chance(0.3) {
+            head = format!("[{head}]");
+        }
+        let rest = list(rng, 1);
+        match &rest[1..rest.len() - 1] {
+            "" => format!("[{head}]"),
+            rest => format!("[{head},{rest}]"),
+        }
+    }
+    let pairs: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            let a = rng.range(0..=10);
+            let b = (a + rng.range(1..=10)) % 11;
+            format!("{}\n{}\n", packet(rng, a), packet(rng, b))
+        })
+        .collect();
+    pairs.join("\n")
+}
+
+/// `size` paths of rock below the source, spread out more the more there are.
+fn rock_paths(rng: &mut Rng, size: usize) -> String {
+    let (spread, depth) = (20 + size as i64 / 3, 10 + size as i64 / 2);
+    let mut s = String::new();
+    for _ in 0..size.max(1) {
+        let mut p = (rng.range(500 - spread..=500 + spread), rng.range(2..=depth));
+        let mut points = vec![format!("{},{}", p.0, p.1)];
+        let mut horizontal = rng.chance(0.5);
+        for _ in 0..rng.range(1..=4) {
+            let step = rng.range(1..=8) * if rng.chance(0.5) { -1 } else { 1 };
+            if horizontal {
+                p.0 += step;
+            } else {
+                p.1 = (p.1 + step).max(2);
+            }
+            horizontal = !horizontal;
+            points.push(format!("{},{}", p.0, p.1));
+        }
+        writeln!(s, "{}", points.join(" -> ")).unwrap();
+    }
+    s
+}
+
+/// `size` sensors in the 0-4000000 square part 2 searches, half of them covering
+/// some of the row part 1 counts.
+fn sensors(rng: &mut Rng, size: usize) -> String {
+    const ROW: i64 = 2_000_000;
+    let mut s = String::new();
+    for _ in 0..size.max(1) {
+        let dist = rng.range(1..=1_000_000);
+        let x = rng.range(0..=4_000_000);
+        let y = match rng.chance(0.5) {
+            true => ROW + rng.range(-dist..=dist),
+            false => rng.range(0..=4_000_000),
+        };
+        // Some of those have their beacon on the row.
+        let dy = match rng.chance(0.2) && (ROW - y).abs() <= dist {
+            true => ROW - y,
+            false => rng.range(-dist..=dist),
+        };
+        let dx = (dist - dy.abs()) * if rng.chance(0.5) { -1 } else { 1 };
+        writeln!(
+            s,
+            "Sensor at x={x}, y={y}: closest beacon is at x={}, y={}",
+            x + dx,
+            y + dy
+        )
+        .unwrap();
+    }
+    s
+}
+
+/// `size` connected valves, at most 15 of them with flow, as in the puzzle.
+fn valves(rng: &mut Rng, size: usize) -> String {
+    let count = size.clamp(2, 26 * 26);
+    let mut names: Vec<String> = vec!["AA".to_string()];
+    let mut taken: HashSet<String> = names.iter().cloned().collect();
+    while names.len() < count {
+        let name: String = (0..2)
+            .map(|_| (b'A' + rng.below(26) as u8) as char)
+            .collect();
+        if taken.insert(name.clone()) {
+            names.push(name);
+        }
+    }
+    let mut tunnels: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); count];
+    let mut connect = |a: usize, b: usize| {
+        if a != b {
+            tunnels[a].insert(b);
+            tunnels[b].insert(a);
+        }
+    };
+    for i in 1..count {
+        connect(i, rng.below(i));
+    }
+    for _ in 0..count / 3 {
+        connect(rng.below(count), rng.below(count));
+    }
+    let mut with_flow: Vec<usize> = (1..count).collect();
+    rng.shuffle(&mut with_flow);
+    with_flow.truncate(15.min(count / 3 + 1));
+    let mut s = String::new();
+    for (i, name) in names.iter().enumerate() {
+        let rate = if with_flow.contains(&i) {
+            rng.range(1..=25)
+        } else {
+            0
+        };
+        let mut to: Vec<&str> = tunnels[i].iter().map(|&j| names[j].as_str()).collect();
+        rng.shuffle(&mut to);
+        let tunnels = match to[..] {
+            [one] => format!("tunnel leads to valve {one}"),
+            _ => format!("tunnels lead to valves {}", to.join(", ")),
+        };
+        writeln!(s, "Valve {name} has flow rate={rate}; {tunnels}").unwrap();
+    }
+    s
+}
+
+/// `size` jets, at least 40. Much shorter patterns can leave a column of the
+/// chamber empty forever, and part 2 never sees the tower repeat.
+fn jets(rng: &mut Rng, size: usize) -> String {
+    let mut s: String = (0..size.max(40)).map(|_| *rng.pick(&['<', '>'])).collect();
+    s.push('\n');
+    s
+}
+
+/// `size` distinct cubes, packed in a box a bit bigger than they'd fill.
+fn cubes(rng: &mut Rng, size: usize) -> String {
+    let side = (2.0 * (size as f64).cbrt()) as i64 + 2;
+    let count = size.max(1).min((side * side * side) as usize);
+    let mut seen = HashSet::new();
+    let mut s = String::new();
+    while seen.len() < count {
+        let cube = (
+            rng.range(0..=side - 1),
+            rng.range(0..=side - 1),
+            rng.range(0..=side - 1),
+        );
+        if seen.insert(cube) {
+            writeln!(s, "{},{},{}", cube.0, cube.1, cube.2).unwrap();
+        }
+    }
+    s
+}
+
+/// `size` blueprints, with costs in the puzzle's ranges.
+fn blueprints(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for i in 1..=size.max(1) {
+        writeln!(
+            s,
+            "Blueprint {i}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+             Each obsidian robot costs {} ore and {} clay. \
+             Each geode robot costs {} ore and {} obsidian.",
+            rng.range(2..=4),
+            rng.range(2..=4),
+            rng.range(2..=4),
+            rng.range(5..=20),
+            rng.range(2..=4),
+            rng.range(7..=20),
+        )
+        .unwrap();
+    }
+    s
+}
+
+/// `size` numbers, exactly one of them 0.
+fn encrypted_file(rng: &mut Rng, size: usize) -> String {
+    let count = size.max(2);
+    let zero = rng.below(count);
+    let mut s = String::new();
+    for i in 0..count {
+        let n = if i == zero {
+            0
+        } else {
+            rng.range(1..=10000) * if rng.chance(0.5) { -1 } else { 1 }
+        };
+        writeln!(s, "{n}").unwrap();
+    }
+    s
+}
+
+/// About `size` operations, with every division exact and `humn` used once. Both
+/// sides of `root` are equal, so the number `humn` yells solves part 2.
+fn monkey_math(rng: &mut Rng, size: usize) -> String {
+    let mut taken: HashSet<String> = HashSet::from(["root".to_string(), "humn".to_string()]);
+    let mut lines = Vec::new();
+    let mut leaves = Vec::new();
+    // Adds a monkey yelling `value`, with `ops` operations below it.
+    fn monkey(
+        rng: &mut Rng,
+        name: String,
+        value: i64,
+        ops: usize,
+        taken: &mut HashSet<String>,
+        lines: &mut Vec<String>,
+        leaves: &mut Vec<usize>,
+    ) {
+        if ops == 0 {
+            leaves.push(lines.len());
+            lines.push(format!("{name}: {value}"));
+            return;
+        }
+        let divisors: Vec<i64> = (2..=9).filter(|d| value % d == 0).collect();
+        let (a, op, b) = match rng.below(4) {
+            0 if value >= 2 => {
+                let a = rng.range(1..=value - 1);
+                (a, '+', value - a)
+            }
+            2 if !divisors.is_empty() => {
+                let d = *rng.pick(&divisors);
+                (value / d, '*', d)
+            }
+            3 if value < 1_000_000_000_000 => {
+                let d = rng.range(2..=9);
+                (value * d, '/', d)
+            }
+            _ => {
+                let b = rng.range(1..=1000);
+                (value + b, '-', b)
+            }
+        };
+        let left_ops = rng.below(ops);
+        let (left, right) = (child(rng, taken), child(rng, taken));
+        lines.push(format!("{name}: {left} {op} {right}"));
+        monkey(rng, left, a, left_ops, taken, lines, leaves);
+        monkey(rng, right, b, ops - 1 - left_ops, taken, lines, leaves);
+    }
+    fn child(rng: &mut Rng, taken: &mut HashSet<String>) -> String {
+        loop {
+            let name = rng.word(4);
+            if taken.insert(name.clone()) {
+                break name;
+            }
+        }
+    }
+    let half = rng.range(1..=50_000);
+    let ops = size.max(1);
+    let left_ops = rng.below(ops);
+    let (left, right) = (child(rng, &mut taken), child(rng, &mut taken));
+    lines.push(format!("root: {left} + {right}"));
+    for (name, ops) in [(left, left_ops), (right, ops - 1 - left_ops)] {
+        monkey(rng, name, half, ops, &mut taken, &mut lines, &mut leaves);
+    }
+    let humn = *rng.pick(&leaves);
+    let (old, value) = lines[humn].split_once(':').unwrap();
+    let (old, line) = (old.to_string(), format!("humn:{value}"));
+    lines[humn] = line;
+    for l in &mut lines {
+        *l = l.replace(&format!(" {old}"), " humn");
+    }
+    rng.shuffle(&mut lines);
+    lines.iter().map(|l| format!("{l}\n")).collect()
+}
+
+/// The puzzle's cube net with 50 wide faces (the only one part 2 folds), and a
+/// path of `size` moves.
+fn monkey_map(rng: &mut Rng, size: usize) -> String {
+    const FACES: [(usize, usize); 6] = [(1, 0), (2, 0), (1, 1), (0, 2), (1, 2), (0, 3)];
+    let mut s = String::new();
+    for y in 0..200 {
+        let width = FACES
+            .iter()
+            .filter(|f| f.1 == y / 50)
+            .map(|f| f.0 + 1)
+            .max();
+        for x in 0..50 * width.unwrap() {
+            let c = if !FACES.contains(&(x / 50, y / 50)) {
+                ' '
+            } else if (x, y) != (50, 0) && rng.chance(0.1) {
+                '#'
+            } else {
+                '.'
+            };
+            s.push(c);
+        }
+        s.push('\n');
+    }
+    s.push('\n');
+    for i in 0..size.max(1) {
+        if i > 0 {
+            s.push(*rng.pick(&['L', 'R']));
+        }
+        write!(s, "{}", rng.range(1..=50)).unwrap();
+    }
+    s.push('\n');
+    s
+}
+
+/// A `size` by `size` grid, about 40% elves.
+fn elves(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut s = String::new();
+    for y in 0..size {
+        for x in 0..size {
+            let elf = (x, y) == (size / 2, size / 2) || rng.chance(0.4);
+            s.push(if elf { '#' } else { '.' });
+        }
+        s.push('\n');
+    }
+    s
+}
+
+/// A basin `size` wide inside its walls and a quarter as high, about a third
+/// blizzards. As in the puzzle, no blizzard blows into the entrance or exit.
+fn basin(rng: &mut Rng, size: usize) -> String {
+    let (width, height) = (size.max(2), (size / 4).max(2));
+    let mut s = format!("#.{}\n", "#".repeat(width));
+    for _ in 0..height {
+        s.push('#');
+        for x in 1..=width {
+            let dirs: &[char] = if x == 1 || x == width {
+                &['<', '>']
+            } else {
+                &['<', '>', '^', 'v']
+            };
+            s.push(if rng.chance(0.35) {
+                *rng.pick(dirs)
+            } else {
+                '.'
+            });
+        }
+        s += "#\n";
+    }
+    s += &"#".repeat(width);
+    s += ".#\n";
+    s
+}
+
+/// `size` numbers.
+fn snafu_numbers(rng: &mut Rng, size: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..size.max(1) {
+        let n = rng.range(1..=10_000_000_000_000) as u64;
+        writeln!(s, "{}", crate::day25::decimal_to_snafu(n)).unwrap();
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::*;
+    use crate::{runner, DAYS};
+    #[test]
+    fn rng_ranges() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.range(-3..=3)));
+            assert!(rng.below(5) < 5);
+        }
+        let mut items: Vec<u32> = (0..10).collect();
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert_eq!(generate(16, 20, 3), generate(16, 20, 3));
+        assert_ne!(generate(16, 20, 3), generate(16, 20, 4));
+        assert_eq!(generate(26, 20, 3), None);
+    }
+    #[test]
+    fn inputs_are_solvable() {
+        for seed in 0..3 {
+            for d in &DAYS {
+                let input = generate(d.number, 12, seed).unwrap();
+                for part in [1, 2] {
+                    // Day 19 takes seconds per blueprint.
+                    if d.number == 19 {
+                        continue;
+                    }
+                    let run = runner::run_part(d, part, &input);
+                    assert!(
+                        run.answer.is_ok(),
+                        "day {} part {part} seed {seed}: {:?}\n{input}",
+                        d.number,
+                        run.answer
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{error::Result, parse};
+use std::ops::{Index, IndexMut};
+
+/// `(x, y)` with `(0, 0)` top left and y growing downwards, as in the input text.
+pub type Pos = (usize, usize);
+
+const DIRS_4: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const DIRS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Dense, row-major 2D grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+    /// Parses a rectangular char map, one cell per char. `f` maps chars to cells,
+    /// `None` is reported as a parse error expecting `expected`.
+    pub fn parse(
+        day: u8,
+        input: &str,
+        expected: &str,
+        f: impl Fn(char) -> Option<T>,
+    ) -> Result<Self> {
+        let rows = parse_rows(day, input, expected, false, f)?;
+        Ok(Self::from_rows(rows))
+    }
+    /// Like `parse`, but rows may be ragged; short rows are padded with `fill`.
+    pub fn parse_padded(
+        day: u8,
+        input: &str,
+        expected: &str,
+        fill: T,
+        f: impl Fn(char) -> Option<T>,
+    ) -> Result<Self>
+    where
+        T: Clone,
+    {
+        let mut rows = parse_rows(day, input, expected, true, f)?;
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        rows.iter_mut().for_each(|r| r.resize(width, fill.clone()));
+        Ok(Self::from_rows(rows))
+    }
+    /// Rows must be non-empty and of equal length.
+    fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        Self {
+            width: rows[0].len(),
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.1 * self.width + pos.0])
+    }
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.1 * self.width + pos.0])
+    }
+    /// `pos` moved by `(dx, dy)`, if that's still inside the grid.
+    pub fn offset(&self, (x, y): Pos, (dx, dy): (isize, isize)) -> Option<Pos> {
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(pos).then_some(pos)
+    }
+    pub fn neighbors_4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRS_4.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+    /// Includes the diagonals.
+    pub fn neighbors_8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRS_8.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+    /// Cells of column `x`, top to bottom. Panics if `x` is past the right edge, like
+    /// `row` past the bottom.
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(x < self.width, "column {x} of a grid {} wide", self.width);
+        self.cells[x..].iter().step_by(self.width)
+    }
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+    /// Cells with their positions, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(&self.cells)
+    }
+    pub fn find(&self, pred: impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, t)| pred(t)).map(|(p, _)| p)
+    }
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+    /// Rows become columns.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..self.width)
+            .flat_map(|x| self.column(x).cloned())
+            .collect();
+        Self {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+    /// One line per row, each ending in a newline.
+    pub fn render(&self, f: impl Fn(&T) -> char) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            s.extend(row.iter().map(&f));
+            s.push('\n');
+        }
+        s
+    }
+}
+
+fn parse_rows<T>(
+    day: u8,
+    input: &str,
+    expected: &str,
+    ragged: bool,
+    f: impl Fn(char) -> Option<T>,
+) -> Result<Vec<Vec<T>>> {
+    let mut rows: Vec<Vec<T>> = Vec::new();
+    for mut l in parse::lines(day, input) {
+        let width = match rows.first() {
+            Some(first) if !ragged => first.len(),
+            _ => usize::MAX,
+        };
+        let mut row = Vec::new();
+        while !l.is_done() && row.len() < width {
+            row.push(l.char_map(expected, &f)?);
+        }
+        l.end()?;
+        if width != usize::MAX && row.len() < width {
+            return Err(l.error(expected));
+        }
+        rows.push(row);
+    }
+    if rows.iter().all(Vec::is_empty) {
+        return Err(parse::missing(day, input, expected));
+    }
+    Ok(rows)
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside {width}x{height} grid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::*;
+    fn digits(input: &str) -> Result<Grid<u32>> {
+        Grid::parse(8, input, "a digit", |c| c.to_digit(10))
+    }
+    #[test]
+    fn parse_and_views() {
+        let grid = digits("123\n456\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.row(1), [4, 5, 6]);
+        assert_eq!(grid.column(1).rev().collect::<Vec<_>>(), [&5, &2]);
+        assert_eq!(grid.find(|&d| d == 4), Some((0, 1)));
+        assert_eq!(
+            grid.transpose()
+                .render(|d| char::from_digit(*d, 10).unwrap()),
+            "14\n25\n36\n"
+        );
+    }
+    #[test]
+    #[should_panic = "column 3 of a grid 3 wide"]
+    fn column_out_of_bounds() {
+        Grid::new(3, 2, 0).column(3).for_each(drop);
+    }
+    #[test]
+    fn neighbors() {
+        let grid = Grid::new(3, 3, ());
+        assert_eq!(
+            grid.neighbors_4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbors_4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbors_8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbors_8((2, 2)).count(), 3);
+    }
+    #[test]
+    fn parse_errors() {
+        let err = |input| digits(input).unwrap_err().to_string();
+        assert_eq!(
+            err("123\n45\n"),
+            "day 08, line 2, column 3: expected a digit, found nothing"
+        );
+        assert_eq!(
+            err("123\n4567\n"),
+            r#"day 08, line 2, column 4: expected end of line, found "7""#
+        );
+        assert_eq!(
+            err("123\n4x6\n"),
+            r#"day 08, line 2, column 2: expected a digit, found "x6""#
+        );
+        let padded = Grid::parse_padded(22, " .\n.#.\n", "tile", ' ', Some).unwrap();
+        assert_eq!(padded.render(|&c| c), " . \n.#.\n");
+    }
+}
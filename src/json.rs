@@ -0,0 +1,286 @@
+use std::fmt::{self, Write};
+
+/// Minimal JSON value, enough for baselines and machine-readable output.
+/// Objects keep their keys in insertion order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Json)>) -> Self {
+        Json::Object(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut p = Parser { text, pos: 0 };
+        let value = p.value()?;
+        p.skip_whitespace();
+        if p.pos < text.len() {
+            return Err(p.error("end of input"));
+        }
+        Ok(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+macro_rules! json_from_num {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Json {
+            fn from(n: $t) -> Self {
+                Json::Number(n as f64)
+            }
+        })*
+    };
+}
+json_from_num!(u8, u32, u64, usize, i32, i64, f64);
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Compact, single-line output.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Number(n) if !n.is_finite() => f.write_str("null"),
+            Json::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Json::Number(n) => write!(f, "{n}"),
+            Json::String(s) => write_str(f, s),
+            Json::Array(a) => {
+                f.write_char('[')?;
+                for (i, v) in a.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_str(f, k)?;
+                    write!(f, ":{v}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, expected: &str) -> String {
+        let found: String = self.text[self.pos..].chars().take(20).collect();
+        format!("offset {}: expected {expected}, found {found:?}", self.pos)
+    }
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    fn try_tag(&mut self, tag: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(tag);
+        if found {
+            self.pos += tag.len();
+        }
+        found
+    }
+    fn tag(&mut self, tag: &str) -> Result<(), String> {
+        if self.try_tag(tag) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("{tag:?}")))
+        }
+    }
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.rest().chars().next() {
+            Some('n') => self.tag("null").map(|_| Json::Null),
+            Some('t') => self.tag("true").map(|_| Json::Bool(true)),
+            Some('f') => self.tag("false").map(|_| Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some('-' | '0'..='9') => self.number(),
+            _ => Err(self.error("a value")),
+        }
+    }
+    fn number(&mut self) -> Result<Json, String> {
+        let len = self
+            .rest()
+            .find(|c: char| !matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+            .unwrap_or(self.rest().len());
+        let n = self.rest()[..len]
+            .parse()
+            .map_err(|_| self.error("a number"))?;
+        self.pos += len;
+        Ok(Json::Number(n))
+    }
+    fn string(&mut self) -> Result<String, String> {
+        self.tag("\"")?;
+        let mut s = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(s);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("a \\u escape"))?
+                        }
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => return Err(self.error("an escape")),
+                    };
+                    s.push(escaped);
+                }
+                c => s.push(c),
+            }
+        }
+        Err(self.error("closing '\"'"))
+    }
+    fn array(&mut self) -> Result<Json, String> {
+        self.tag("[")?;
+        let mut items = Vec::new();
+        if self.try_tag("]") {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.try_tag("]") {
+                return Ok(Json::Array(items));
+            }
+            self.tag(",")?;
+        }
+    }
+    fn object(&mut self) -> Result<Json, String> {
+        self.tag("{")?;
+        let mut fields = Vec::new();
+        if self.try_tag("}") {
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.tag(":")?;
+            fields.push((key, self.value()?));
+            if self.try_tag("}") {
+                return Ok(Json::Object(fields));
+            }
+            self.tag(",")?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::*;
+    #[test]
+    fn round_trip() {
+        let value = Json::object([
+            ("day", Json::from(10)),
+            ("answer", Json::from("##..\n\"x\"")),
+            ("seconds", Json::from(0.25)),
+            ("ok", Json::from(true)),
+            ("stats", Json::Array(vec![Json::Null, Json::from(-3)])),
+        ]);
+        let text = value.to_string();
+        assert_eq!(
+            text,
+            r###"{"day":10,"answer":"##..\n\"x\"","seconds":0.25,"ok":true,"stats":[null,-3]}"###
+        );
+        assert_eq!(Json::parse(&text).unwrap(), value);
+    }
+    #[test]
+    fn parse_whitespace_and_errors() {
+        let value = Json::parse(" { \"a\" : [ 1 , 2e3 ] , \"b\":\"\\u0041\" } ").unwrap();
+        assert_eq!(
+            value.get("a").unwrap().as_array().unwrap()[1],
+            Json::from(2000)
+        );
+        assert_eq!(value.get("b").unwrap().as_str(), Some("A"));
+        assert_eq!(
+            Json::parse("[1,]").unwrap_err(),
+            r#"offset 3: expected a value, found "]""#
+        );
+    }
+}
@@ -1,3 +1,7 @@
+pub mod bench;
+pub mod budget;
+pub mod client;
+pub mod cycle;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -23,3 +27,190 @@ pub mod day22;
 pub mod day23;
 pub mod day24;
 pub mod day25;
+pub mod error;
+pub mod generator;
+pub mod grid;
+pub mod json;
+pub mod log;
+pub mod manifest;
+pub mod parallel;
+mod parse;
+pub mod render;
+pub mod runner;
+pub mod search;
+pub mod simulation;
+pub mod solution;
+pub mod stats;
+pub mod strategy;
+
+use budget::{Bounded, Budget};
+use error::Result;
+use render::Renderer;
+use solution::{Answer, Solution};
+use std::io::BufRead;
+use strategy::Strategy;
+
+/// Draws the frames of a day's simulation for the given part.
+pub type VisualizeFn = fn(&str, u8, &mut dyn Renderer) -> Result<()>;
+
+/// Solves the given part within a budget, for days that can be cut short.
+pub type BudgetedFn = fn(&str, u8, &Budget) -> Result<Bounded<Answer>>;
+
+/// Registry entry for one day.
+pub struct Day {
+    pub number: u8,
+    /// Default input file, relative to the crate root.
+    pub input: &'static str,
+    pub part_1: fn(&str) -> Result<Answer>,
+    pub part_2: fn(&str) -> Result<Answer>,
+    pub read_1: fn(&mut dyn BufRead) -> Result<Answer>,
+    pub read_2: fn(&mut dyn BufRead) -> Result<Answer>,
+    pub visualize: Option<VisualizeFn>,
+    pub budgeted: Option<BudgetedFn>,
+    /// Alternative implementations to cross-check, for days that have them.
+    pub strategies: &'static [Strategy],
+}
+
+fn run_part_1<S: Solution>(input: &str) -> Result<Answer> {
+    S::part_1(input).map(Into::into)
+}
+fn run_part_2<S: Solution>(input: &str) -> Result<Answer> {
+    S::part_2(input).map(Into::into)
+}
+fn read_part_1<S: Solution>(input: &mut dyn BufRead) -> Result<Answer> {
+    S::part_1_read(input).map(Into::into)
+}
+fn read_part_2<S: Solution>(input: &mut dyn BufRead) -> Result<Answer> {
+    S::part_2_read(input).map(Into::into)
+}
+
+impl Day {
+    const fn new<S: Solution>(number: u8, input: &'static str) -> Self {
+        Self {
+            number,
+            input,
+            part_1: run_part_1::<S>,
+            part_2: run_part_2::<S>,
+            read_1: read_part_1::<S>,
+            read_2: read_part_2::<S>,
+            visualize: None,
+            budgeted: None,
+            strategies: &[],
+        }
+    }
+    const fn with_visualize(self, visualize: VisualizeFn) -> Self {
+        Self {
+            visualize: Some(visualize),
+            ..self
+        }
+    }
+    const fn with_budget(self, budgeted: BudgetedFn) -> Self {
+        Self {
+            budgeted: Some(budgeted),
+            ..self
+        }
+    }
+    const fn with_strategies(self, strategies: &'static [Strategy]) -> Self {
+        Self { strategies, ..self }
+    }
+    /// Runs part 1 or 2, `None` for any other part number.
+    pub fn run(&self, part: u8, input: &str) -> Option<Result<Answer>> {
+        match part {
+            1 => Some((self.part_1)(input)),
+            2 => Some((self.part_2)(input)),
+            _ => None,
+        }
+    }
+    /// Like `run`, stopping early with the best answer so far if `budget` runs out.
+    /// Days that can't be cut short ignore the budget.
+    pub fn run_within(
+        &self,
+        part: u8,
+        input: &str,
+        budget: &Budget,
+    ) -> Option<Result<Bounded<Answer>>> {
+        match (self.budgeted, part) {
+            (Some(budgeted), 1 | 2) => Some(budgeted(input, part, budget)),
+            _ => Some(self.run(part, input)?.map(Bounded::complete)),
+        }
+    }
+    /// Like `run`, reading the input as the part goes.
+    pub fn run_read(&self, part: u8, input: &mut dyn BufRead) -> Option<Result<Answer>> {
+        match part {
+            1 => Some((self.read_1)(input)),
+            2 => Some((self.read_2)(input)),
+            _ => None,
+        }
+    }
+}
+
+pub const DAYS: [Day; 25] = [
+    Day::new::<day01::Day01>(1, "input/day01"),
+    Day::new::<day02::Day02>(2, "input/day02"),
+    Day::new::<day03::Day03>(3, "input/day03"),
+    Day::new::<day04::Day04>(4, "input/day04"),
+    Day::new::<day05::Day05>(5, "input/day05_modified"),
+    Day::new::<day06::Day06>(6, "input/day06"),
+    Day::new::<day07::Day07>(7, "input/day07"),
+    Day::new::<day08::Day08>(8, "input/day08").with_strategies(&day08::STRATEGIES),
+    Day::new::<day09::Day09>(9, "input/day09"),
+    Day::new::<day10::Day10>(10, "input/day10").with_visualize(day10::visualize),
+    Day::new::<day11::Day11>(11, "input/day11"),
+    Day::new::<day12::Day12>(12, "input/day12"),
+    Day::new::<day13::Day13>(13, "input/day13"),
+    Day::new::<day14::Day14>(14, "input/day14").with_visualize(day14::visualize),
+    Day::new::<day15::Day15>(15, "input/day15").with_strategies(&day15::STRATEGIES),
+    Day::new::<day16::Day16>(16, "input/day16")
+        .with_budget(day16::solve_within)
+        .with_strategies(&day16::STRATEGIES),
+    Day::new::<day17::Day17>(17, "input/day17").with_visualize(day17::visualize),
+    Day::new::<day18::Day18>(18, "input/day18"),
+    Day::new::<day19::Day19>(19, "input/day19").with_budget(day19::solve_within),
+    Day::new::<day20::Day20>(20, "input/day20").with_strategies(&day20::STRATEGIES),
+    Day::new::<day21::Day21>(21, "input/day21"),
+    Day::new::<day22::Day22>(22, "input/day22").with_visualize(day22::visualize),
+    Day::new::<day23::Day23>(23, "input/day23").with_visualize(day23::visualize),
+    Day::new::<day24::Day24>(24, "input/day24")
+        .with_visualize(day24::visualize)
+        .with_budget(day24::solve_within),
+    Day::new::<day25::Day25>(25, "input/day25"),
+];
+
+pub fn day(number: u8) -> Option<&'static Day> {
+    DAYS.get(number.checked_sub(1)? as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::fs;
+    #[test]
+    fn days_in_order() {
+        for (i, d) in DAYS.iter().enumerate() {
+            assert_eq!(d.number as usize, i + 1);
+            assert!(fs::metadata(d.input).is_ok(), "missing {}", d.input);
+        }
+    }
+    #[test]
+    fn run_by_number() {
+        let input = fs::read_to_string("input/example01").unwrap();
+        assert_eq!(
+            day(1).unwrap().run(2, &input),
+            Some(Ok(Answer::Number(45000)))
+        );
+        assert!(day(1).unwrap().run(3, &input).is_none());
+        assert!(day(26).is_none());
+    }
+    #[test]
+    fn run_from_reader() {
+        // Day 4 reads everything in first, day 1 streams.
+        let input = fs::read_to_string("input/example04").unwrap();
+        let d = day(4).unwrap();
+        assert_eq!(d.run_read(1, &mut input.as_bytes()), d.run(1, &input));
+        let mut bad = &[b'1', 0xff, b'\n'][..];
+        assert!(matches!(
+            day(1).unwrap().run_read(1, &mut bad),
+            Some(Err(error::Error::Io(_)))
+        ));
+    }
+}
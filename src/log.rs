@@ -0,0 +1,154 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    sync::{
+        atomic::{AtomicU8, Ordering::Relaxed},
+        RwLock,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn parse(s: &str) -> Result<u8, String> {
+        Ok(match s {
+            "off" => 0,
+            "error" => Level::Error as u8,
+            "warn" => Level::Warn as u8,
+            "info" => Level::Info as u8,
+            "debug" => Level::Debug as u8,
+            "trace" => Level::Trace as u8,
+            bad => return Err(format!("bad log level: {bad}")),
+        })
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Most verbose level logged anywhere, 0 for nothing. Checked before the filter.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(0);
+/// Level for all targets, then levels for single targets.
+static FILTER: RwLock<(u8, Vec<(String, u8)>)> = RwLock::new((0, Vec::new()));
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Sets what gets logged, from a spec like `info` or `warn,day22=trace`: a level for
+/// every target, and levels for single targets. Nothing is logged by default.
+pub fn set_filter(spec: &str) -> Result<(), String> {
+    let mut default = 0;
+    let mut targets = Vec::new();
+    for item in spec.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+        match item.split_once('=') {
+            Some((target, level)) => targets.push((target.to_string(), Level::parse(level)?)),
+            None => default = Level::parse(item)?,
+        }
+    }
+    let max = targets.iter().map(|t| t.1).fold(default, u8::max);
+    *FILTER.write().unwrap() = (default, targets);
+    MAX_LEVEL.store(max, Relaxed);
+    Ok(())
+}
+
+/// The top module in a module path, e.g. `day22` for its tests too.
+pub fn target(module_path: &'static str) -> &'static str {
+    module_path.split("::").nth(1).unwrap_or(module_path)
+}
+
+pub fn enabled(level: Level, target: &str) -> bool {
+    if CAPTURED.with_borrow(Option::is_some) {
+        return true;
+    }
+    if level as u8 > MAX_LEVEL.load(Relaxed) {
+        return false;
+    }
+    let filter = FILTER.read().unwrap();
+    let allowed = filter
+        .1
+        .iter()
+        .find(|(t, _)| t == target)
+        .map_or(filter.0, |t| t.1);
+    level as u8 <= allowed
+}
+
+/// Writes one line to stderr, or to what `capture` collects.
+pub fn write(level: Level, target: &str, message: fmt::Arguments) {
+    let line = format!("[{level} {target}] {message}");
+    CAPTURED.with_borrow_mut(|captured| match captured {
+        Some(lines) => lines.push(line),
+        None => eprintln!("{line}"),
+    });
+}
+
+/// Runs `f` with everything it logs on this thread collected instead of written,
+/// whatever the filter.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let outer = CAPTURED.replace(Some(Vec::new()));
+    let result = f();
+    let lines = CAPTURED.replace(outer).unwrap_or_default();
+    (result, lines)
+}
+
+/// Logs under the calling module's name, if its level is enabled for it. The
+/// arguments are only evaluated then.
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {{
+        let target = $crate::log::target(module_path!());
+        if $crate::log::enabled($level, target) {
+            $crate::log::write($level, target, format_args!($($arg)+));
+        }
+    }};
+}
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Info, $($arg)+) };
+}
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Debug, $($arg)+) };
+}
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Trace, $($arg)+) };
+}
+pub(crate) use {debug, info, log, trace};
+
+#[cfg(test)]
+mod tests {
+    use crate::log::*;
+    #[test]
+    fn filter_and_capture() {
+        assert!(set_filter("warn,day22=nope").is_err());
+        set_filter("warn,day22=trace").unwrap();
+        assert!(enabled(Level::Trace, "day22"));
+        assert!(enabled(Level::Warn, "day21"));
+        assert!(!enabled(Level::Info, "day21"));
+        set_filter("").unwrap();
+        assert!(!enabled(Level::Error, "day22"));
+        assert_eq!(target("advent_of_code_2022::day22::tests"), "day22");
+        let mut evaluated = false;
+        debug!("{}", {
+            evaluated = true;
+            1
+        });
+        assert!(!evaluated);
+        let ((), lines) = capture(|| debug!("at {}", 7));
+        assert_eq!(lines, ["[debug log] at 7"]);
+    }
+}
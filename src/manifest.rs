@@ -0,0 +1,183 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Default manifest, relative to the crate root.
+pub const DEFAULT_PATH: &str = "input/answers";
+
+/// Expected answer for one part of one day on one input file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub day: u8,
+    pub part: u8,
+    /// Input file name, relative to the manifest's directory.
+    pub input: String,
+    pub expected: Expected,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expected {
+    Inline(String),
+    /// Written `@name`: the answer is the contents of a file next to the manifest.
+    File(String),
+}
+
+/// The answers file: lines of `day part input answer`, `#` starts a comment.
+pub struct Manifest {
+    dir: PathBuf,
+    pub entries: Vec<Entry>,
+}
+
+fn parse_entry(line: &str) -> Result<Entry, String> {
+    let mut fields = line.splitn(4, ' ').map(str::trim);
+    let mut field = |name: &str| {
+        fields
+            .next()
+            .filter(|f| !f.is_empty())
+            .ok_or_else(|| format!("missing {name}"))
+    };
+    let day = field("day")?;
+    let day = day
+        .parse()
+        .ok()
+        .filter(|d| (1..=25).contains(d))
+        .ok_or_else(|| format!("bad day: {day}"))?;
+    let part = match field("part")? {
+        "1" => 1,
+        "2" => 2,
+        bad => return Err(format!("bad part: {bad}")),
+    };
+    let input = field("input")?.to_string();
+    let answer = field("answer")?;
+    let expected = match answer.strip_prefix('@') {
+        Some(file) => Expected::File(file.to_string()),
+        None => Expected::Inline(answer.to_string()),
+    };
+    Ok(Entry {
+        day,
+        part,
+        input,
+        expected,
+    })
+}
+
+impl Manifest {
+    /// `dir` is where input and answer files are looked up.
+    pub fn parse(dir: impl Into<PathBuf>, text: &str) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            entries.push(parse_entry(line).map_err(|e| format!("line {}: {e}", i + 1))?);
+        }
+        Ok(Self {
+            dir: dir.into(),
+            entries,
+        })
+    }
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        Self::parse(dir, &text).map_err(|e| format!("{}, {e}", path.display()))
+    }
+    pub fn find(&self, day: u8, part: u8, input: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|e| e.day == day && e.part == part && e.input == input)
+    }
+    pub fn input_path(&self, entry: &Entry) -> PathBuf {
+        self.dir.join(&entry.input)
+    }
+    pub fn read_input(&self, entry: &Entry) -> Result<String, String> {
+        let path = self.input_path(entry);
+        fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))
+    }
+    pub fn expected(&self, entry: &Entry) -> Result<String, String> {
+        match &entry.expected {
+            Expected::Inline(answer) => Ok(answer.clone()),
+            Expected::File(file) => {
+                let path = self.dir.join(file);
+                fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))
+            }
+        }
+    }
+}
+
+/// Runs the registered solution on a manifest entry and asserts it gives the expected answer.
+#[cfg(test)]
+pub(crate) fn check(day: u8, part: u8, input: &str) {
+    let manifest = Manifest::load(DEFAULT_PATH).unwrap();
+    let entry = manifest
+        .find(day, part, input)
+        .unwrap_or_else(|| panic!("no answer for day {day} part {part} on {input}"));
+    let text = manifest.read_input(entry).unwrap();
+    let answer = crate::day(day).unwrap().run(part, &text).unwrap().unwrap();
+    assert_eq!(answer.to_string(), manifest.expected(entry).unwrap());
+}
+
+/// Like `check`, but streaming the input file into the solution.
+#[cfg(test)]
+pub(crate) fn check_read(day: u8, part: u8, input: &str) {
+    let manifest = Manifest::load(DEFAULT_PATH).unwrap();
+    let entry = manifest
+        .find(day, part, input)
+        .unwrap_or_else(|| panic!("no answer for day {day} part {part} on {input}"));
+    let file = std::fs::File::open(manifest.input_path(entry)).unwrap();
+    let mut reader = std::io::BufReader::new(file);
+    let answer = crate::day(day)
+        .unwrap()
+        .run_read(part, &mut reader)
+        .unwrap()
+        .unwrap();
+    assert_eq!(answer.to_string(), manifest.expected(entry).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manifest::*;
+    #[test]
+    fn parse_entries() {
+        let text = "# comment\n\n5 1 day05_modified VWLCWGSDQ\n10 2 day10 @day10_image # image\n";
+        let manifest = Manifest::parse("input", text).unwrap();
+        assert_eq!(
+            manifest.entries,
+            [
+                Entry {
+                    day: 5,
+                    part: 1,
+                    input: "day05_modified".to_string(),
+                    expected: Expected::Inline("VWLCWGSDQ".to_string()),
+                },
+                Entry {
+                    day: 10,
+                    part: 2,
+                    input: "day10".to_string(),
+                    expected: Expected::File("day10_image".to_string()),
+                }
+            ]
+        );
+        assert_eq!(
+            manifest.input_path(&manifest.entries[1]),
+            Path::new("input/day10")
+        );
+    }
+    #[test]
+    fn parse_errors() {
+        let err = |text| Manifest::parse("", text).err().unwrap();
+        assert_eq!(err("1 1 day01 1\n26 1 day26 1"), "line 2: bad day: 26");
+        assert_eq!(err("1 3 day01 1"), "line 1: bad part: 3");
+        assert_eq!(err("1 1 day01"), "line 1: missing answer");
+    }
+    #[test]
+    fn every_input_exists() {
+        let manifest = Manifest::load(DEFAULT_PATH).unwrap();
+        for entry in &manifest.entries {
+            assert!(manifest.read_input(entry).is_ok(), "{}", entry.input);
+            assert!(manifest.expected(entry).is_ok(), "{:?}", entry.expected);
+        }
+    }
+}
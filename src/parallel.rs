@@ -0,0 +1,120 @@
+use crate::stats;
+use std::{
+    num::NonZeroUsize,
+    panic,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed},
+        Mutex,
+    },
+    thread,
+};
+
+/// 0 means one per core.
+static WORKERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets how many threads `map` and `find_map` use at most, 0 for one per core.
+/// With 1, everything runs on the calling thread.
+pub fn set_workers(workers: usize) {
+    WORKERS.store(workers, Relaxed);
+}
+
+pub fn workers() -> usize {
+    match WORKERS.load(Relaxed) {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        n => n,
+    }
+}
+
+/// `f` applied to every item, spread over the workers. Results are in item order.
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let mut results = run(items, |item| Some(f(item)), false);
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// A `Some` that `f` returns for one of the items, without waiting for the rest once
+/// one is found. If more than one is found, the one for the earliest item.
+pub fn find_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> Option<R> + Sync) -> Option<R> {
+    let results = run(items, f, true);
+    results.into_iter().min_by_key(|(i, _)| *i).map(|(_, r)| r)
+}
+
+/// The `Some` results of `f`, with the index of their item. Workers take the next
+/// item as they finish one. Stats reported on the workers are passed on to the caller,
+/// and a panic on a worker is resumed on the caller.
+fn run<T: Sync, R: Send>(
+    items: &[T],
+    f: impl Fn(&T) -> Option<R> + Sync,
+    stop_at_first: bool,
+) -> Vec<(usize, R)> {
+    let workers = workers().min(items.len());
+    if workers <= 1 {
+        let mut results = items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| Some((i, f(item)?)));
+        return match stop_at_first {
+            true => results.next().into_iter().collect(),
+            false => results.collect(),
+        };
+    }
+    let next = AtomicUsize::new(0);
+    let found = AtomicBool::new(false);
+    let results = Mutex::new(Vec::new());
+    let work = || {
+        while !found.load(Relaxed) {
+            let i = next.fetch_add(1, Relaxed);
+            let Some(item) = items.get(i) else {
+                break;
+            };
+            if let Some(r) = f(item) {
+                results.lock().unwrap().push((i, r));
+                found.store(stop_at_first, Relaxed);
+            }
+        }
+    };
+    let reports: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| scope.spawn(|| stats::collect(work).1))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+            .collect()
+    });
+    for (name, s) in reports.into_iter().flatten() {
+        stats::report(name, s);
+    }
+    results.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parallel::*;
+    use crate::stats::SearchStats;
+    #[test]
+    fn map_in_order() {
+        // Enough to use threads even on one core.
+        set_workers(4);
+        let items: Vec<u64> = (0..100).collect();
+        let ((squares, found), report) = stats::collect(|| {
+            let squares = map(&items, |n| {
+                stats::report("square", SearchStats::default());
+                n * n
+            });
+            (
+                squares,
+                find_map(&items, |&n| (n > 10 && n.is_multiple_of(7)).then_some(n)),
+            )
+        });
+        assert_eq!(squares, items.iter().map(|n| n * n).collect::<Vec<_>>());
+        assert!(found.is_some_and(|n| n > 10 && n.is_multiple_of(7)));
+        assert_eq!(report.len(), 1);
+        assert_eq!(find_map(&items, |_| None::<u64>), None);
+        let panicked = panic::catch_unwind(|| map(&items, |&n| assert!(n != 50)));
+        assert!(panicked.is_err());
+    }
+}
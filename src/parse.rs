@@ -0,0 +1,191 @@
+use crate::error::{Error, ParseError, Result};
+use std::{io::BufRead, str::FromStr};
+
+/// Cursor over one line of input. Every failure is reported as a `ParseError`
+/// pointing at the column where the cursor stood.
+#[derive(Clone)]
+pub(crate) struct Line<'a> {
+    day: u8,
+    number: usize,
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Line<'a> {
+    /// `number` is the 1-based line number, used in errors.
+    pub fn new(day: u8, number: usize, text: &'a str) -> Self {
+        Self {
+            day,
+            number,
+            text,
+            pos: 0,
+        }
+    }
+    pub fn number(&self) -> usize {
+        self.number
+    }
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+    pub fn is_done(&self) -> bool {
+        self.pos == self.text.len()
+    }
+    fn error_at(&self, pos: usize, expected: impl Into<String>) -> Error {
+        ParseError {
+            day: self.day,
+            line: self.number,
+            column: self.text[..pos].chars().count() + 1,
+            expected: expected.into(),
+            found: self.text[pos..].chars().take(20).collect(),
+        }
+        .into()
+    }
+    /// Error at the current position.
+    pub fn error(&self, expected: impl Into<String>) -> Error {
+        self.error_at(self.pos, expected)
+    }
+    pub fn tag(&mut self, tag: &str) -> Result<()> {
+        if self.try_tag(tag) {
+            Ok(())
+        } else {
+            Err(self.error(format!("{tag:?}")))
+        }
+    }
+    pub fn try_tag(&mut self, tag: &str) -> bool {
+        let found = self.rest().starts_with(tag);
+        if found {
+            self.pos += tag.len();
+        }
+        found
+    }
+    /// Parses an optionally signed decimal integer.
+    pub fn int<T: FromStr>(&mut self) -> Result<T> {
+        let start = self.pos;
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with(['-', '+']));
+        let digits = rest[sign..].bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(self.error("a number"));
+        }
+        let n = rest[..sign + digits]
+            .parse()
+            .map_err(|_| self.error_at(start, "a number in range"))?;
+        self.pos += sign + digits;
+        Ok(n)
+    }
+    /// Takes a non-empty run of alphanumeric chars.
+    pub fn word(&mut self) -> Result<&'a str> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("a word"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+    /// Maps the next char through `f`, erroring with `expected` if it returns `None`.
+    pub fn char_map<T>(&mut self, expected: &str, f: impl Fn(char) -> Option<T>) -> Result<T> {
+        let res = self
+            .rest()
+            .chars()
+            .next()
+            .and_then(|c| f(c).map(|t| (c, t)));
+        let (c, t) = res.ok_or_else(|| self.error(expected))?;
+        self.pos += c.len_utf8();
+        Ok(t)
+    }
+    pub fn end(&self) -> Result<()> {
+        if self.is_done() {
+            Ok(())
+        } else {
+            Err(self.error("end of line"))
+        }
+    }
+}
+
+/// Numbered lines of `text`, which starts at 1-based line `first`.
+pub(crate) fn lines_from(day: u8, first: usize, text: &str) -> impl Iterator<Item = Line<'_>> {
+    text.lines()
+        .enumerate()
+        .map(move |(i, l)| Line::new(day, first + i, l))
+}
+
+pub(crate) fn lines(day: u8, input: &str) -> impl Iterator<Item = Line<'_>> {
+    lines_from(day, 1, input)
+}
+
+/// Parses lines with `f` as they're read, so the whole input never has to be in memory.
+pub(crate) fn read_lines<'r, T>(
+    day: u8,
+    input: &'r mut dyn BufRead,
+    mut f: impl FnMut(Line) -> Result<T> + 'r,
+) -> impl Iterator<Item = Result<T>> + 'r {
+    input
+        .lines()
+        .enumerate()
+        .map(move |(i, text)| f(Line::new(day, i + 1, &text?)))
+}
+
+/// Blank line separated blocks, with the 1-based line number each block starts at.
+pub(crate) fn blocks(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.split("\n\n").scan(1, |line, block| {
+        let first = *line;
+        *line += block.matches('\n').count() + 2;
+        Some((first, block))
+    })
+}
+
+/// Error for something that should have been in the input but wasn't.
+pub(crate) fn missing(day: u8, input: &str, expected: impl Into<String>) -> Error {
+    ParseError {
+        day,
+        line: input.lines().count() + 1,
+        column: 1,
+        expected: expected.into(),
+        found: String::new(),
+    }
+    .into()
+}
+
+/// Parses a single line on its own, e.g. for puzzles with one line of input.
+pub(crate) fn single<T>(day: u8, input: &str, f: impl FnOnce(&mut Line) -> Result<T>) -> Result<T> {
+    let mut line = Line::new(day, 1, input.trim_end());
+    let t = f(&mut line)?;
+    line.end()?;
+    Ok(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::*;
+    #[test]
+    fn block_line_numbers() {
+        let blocks: Vec<_> = blocks("a\nb\n\nc\n\nd\ne\n").map(|(l, _)| l).collect();
+        assert_eq!(blocks, [1, 4, 6]);
+    }
+    #[test]
+    fn error_position() {
+        let mut l = Line::new(5, 3, "move 1 frm 2 to 3");
+        l.tag("move ").unwrap();
+        l.int::<u32>().unwrap();
+        let Err(Error::Parse(e)) = l.tag(" from ") else {
+            panic!()
+        };
+        assert_eq!((e.day, e.line, e.column), (5, 3, 7));
+        assert_eq!(
+            e.to_string(),
+            r#"day 05, line 3, column 7: expected " from ", found " frm 2 to 3""#
+        );
+    }
+    #[test]
+    fn day_reports_bad_line() {
+        let input = "2-4,6-8\n2-3,4-5\n5-7,7-x\n";
+        let err = crate::day04::part_1(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"day 04, line 3, column 7: expected a number, found "x""#
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use crate::grid::Grid;
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    pub const DARK: Color = Color(60, 60, 60);
+    pub const WHITE: Color = Color(230, 230, 230);
+    pub const GRAY: Color = Color(140, 140, 140);
+    pub const RED: Color = Color(220, 50, 50);
+    pub const GREEN: Color = Color(50, 200, 80);
+    pub const BLUE: Color = Color(80, 140, 240);
+    pub const YELLOW: Color = Color(240, 200, 60);
+}
+
+/// One cell of a frame: a char for text output, a color for everything else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pixel {
+    pub ch: char,
+    pub color: Color,
+}
+
+impl Pixel {
+    pub const EMPTY: Pixel = Pixel::new('.', Color::DARK);
+    pub const fn new(ch: char, color: Color) -> Self {
+        Self { ch, color }
+    }
+}
+
+pub type Canvas = Grid<Pixel>;
+
+/// A blank canvas.
+pub fn canvas(width: usize, height: usize) -> Canvas {
+    Grid::new(width, height, Pixel::EMPTY)
+}
+
+/// Receives the frames of a visualization, one at a time.
+pub trait Renderer {
+    fn frame(&mut self, canvas: &Canvas) -> io::Result<()>;
+}
+
+impl<R: Renderer + ?Sized> Renderer for Box<R> {
+    fn frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        (**self).frame(canvas)
+    }
+}
+
+/// Plain chars, frames separated by a blank line.
+pub struct Text<W: Write> {
+    out: W,
+}
+
+impl<W: Write> Text<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+impl<W: Write> Renderer for Text<W> {
+    fn frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        writeln!(self.out, "{}", canvas.render(|p| p.ch))
+    }
+}
+
+/// Colored chars for a terminal, redrawing in place with `delay` between frames.
+pub struct Ansi<W: Write> {
+    out: W,
+    delay: Duration,
+}
+
+impl<W: Write> Ansi<W> {
+    pub fn new(out: W, delay: Duration) -> Self {
+        Self { out, delay }
+    }
+}
+
+impl<W: Write> Renderer for Ansi<W> {
+    fn frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        let mut s = String::from("\x1b[H\x1b[2J");
+        for row in canvas.rows() {
+            let mut color = None;
+            for p in row {
+                if color != Some(p.color) {
+                    let Color(r, g, b) = p.color;
+                    s += &format!("\x1b[38;2;{r};{g};{b}m");
+                    color = Some(p.color);
+                }
+                s.push(p.ch);
+            }
+            s += "\x1b[0m\n";
+        }
+        self.out.write_all(s.as_bytes())?;
+        self.out.flush()?;
+        thread::sleep(self.delay);
+        Ok(())
+    }
+}
+
+/// Numbered binary PPM images in a directory, each cell `scale` pixels wide.
+pub struct Ppm {
+    dir: PathBuf,
+    scale: usize,
+    frames: usize,
+}
+
+impl Ppm {
+    pub fn new(dir: impl Into<PathBuf>, scale: usize) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            scale: scale.max(1),
+            frames: 0,
+        })
+    }
+}
+
+impl Renderer for Ppm {
+    fn frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        let (width, height) = (canvas.width() * self.scale, canvas.height() * self.scale);
+        let mut data = format!("P6\n{width} {height}\n255\n").into_bytes();
+        for row in canvas.rows() {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|p| [p.color.0, p.color.1, p.color.2].repeat(self.scale))
+                .collect();
+            for _ in 0..self.scale {
+                data.extend_from_slice(&line);
+            }
+        }
+        let path = self.dir.join(format!("frame_{:05}.ppm", self.frames));
+        self.frames += 1;
+        fs::write(path, data)
+    }
+}
+
+/// Passes on only every `n`th frame, for simulations with many small steps.
+pub struct EveryNth<R> {
+    inner: R,
+    n: usize,
+    seen: usize,
+}
+
+impl<R: Renderer> EveryNth<R> {
+    pub fn new(inner: R, n: usize) -> Self {
+        Self {
+            inner,
+            n: n.max(1),
+            seen: 0,
+        }
+    }
+}
+
+impl<R: Renderer> Renderer for EveryNth<R> {
+    fn frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        self.seen += 1;
+        if (self.seen - 1).is_multiple_of(self.n) {
+            self.inner.frame(canvas)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Keeps the frames in memory, for tests.
+#[cfg(test)]
+pub(crate) struct Frames(pub Vec<Canvas>);
+
+#[cfg(test)]
+impl Renderer for Frames {
+    fn frame(&mut self, canvas: &Canvas) -> io::Result<()> {
+        self.0.push(canvas.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::*;
+    fn two_by_one() -> Canvas {
+        let mut canvas = canvas(2, 1);
+        canvas[(1, 0)] = Pixel::new('#', Color(1, 2, 3));
+        canvas
+    }
+    #[test]
+    fn text_and_ansi() {
+        let mut out = Vec::new();
+        Text::new(&mut out).frame(&two_by_one()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), ".#\n\n");
+        let mut out = Vec::new();
+        Ansi::new(&mut out, Duration::ZERO)
+            .frame(&two_by_one())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b[H\x1b[2J\x1b[38;2;60;60;60m.\x1b[38;2;1;2;3m#\x1b[0m\n"
+        );
+    }
+    #[test]
+    fn ppm_frames() {
+        let dir = std::env::temp_dir().join(format!("aoc_ppm_{}", std::process::id()));
+        let mut ppm = Ppm::new(&dir, 2).unwrap();
+        ppm.frame(&two_by_one()).unwrap();
+        let data = fs::read(dir.join("frame_00000.ppm")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&data[..header.len()], header);
+        assert_eq!(data.len(), header.len() + 4 * 2 * 3);
+        assert_eq!(&data[header.len() + 6..header.len() + 9], [1, 2, 3]);
+    }
+    #[test]
+    fn every_nth() {
+        let mut frames = EveryNth::new(Frames(Vec::new()), 2);
+        for _ in 0..5 {
+            frames.frame(&two_by_one()).unwrap();
+        }
+        assert_eq!(frames.inner.0.len(), 3);
+    }
+}
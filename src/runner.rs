@@ -0,0 +1,255 @@
+use crate::{
+    budget::{Bounded, Budget},
+    day, error,
+    json::Json,
+    manifest::{Entry, Manifest},
+    solution::Answer,
+    stats::{self, Report},
+    strategy::Strategy,
+    Day,
+};
+use std::{
+    fmt,
+    io::BufRead,
+    panic::{self, AssertUnwindSafe, UnwindSafe},
+    sync::{atomic::AtomicBool, atomic::Ordering::Relaxed, mpsc, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Result of running one part of one day.
+pub struct Run {
+    pub day: u8,
+    pub part: u8,
+    /// The answer, or the error or panic message.
+    pub answer: Result<Answer, String>,
+    pub elapsed: Duration,
+    /// False if the answer is only the best found before the budget ran out.
+    pub complete: bool,
+    /// What the part's searches reported, if it has any.
+    pub stats: Report,
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+/// Runs one part, catching panics so one broken day doesn't stop a run-all.
+pub fn run_part(day: &Day, part: u8, input: &str) -> Run {
+    timed(day, part, || complete(day.run(part, input)))
+}
+
+/// Like `run_part`, stopping early if `budget` runs out, for days that support it.
+pub fn run_part_within(day: &Day, part: u8, input: &str, budget: &Budget) -> Run {
+    timed(
+        day,
+        part,
+        AssertUnwindSafe(|| day.run_within(part, input, budget)),
+    )
+}
+
+/// Like `run_part`, reading the input as the part goes. Reading time is included.
+pub fn run_part_read(day: &Day, part: u8, input: &mut dyn BufRead) -> Run {
+    timed(
+        day,
+        part,
+        AssertUnwindSafe(|| complete(day.run_read(part, input))),
+    )
+}
+
+/// Runs one of a part's strategies, like `run_part`.
+pub fn run_strategy(day: &Day, strategy: &Strategy, input: &str) -> Run {
+    timed(day, strategy.part, || complete(Some((strategy.run)(input))))
+}
+
+fn complete(answer: Option<error::Result<Answer>>) -> Option<error::Result<Bounded<Answer>>> {
+    answer.map(|answer| answer.map(Bounded::complete))
+}
+
+fn timed(
+    day: &Day,
+    part: u8,
+    f: impl FnOnce() -> Option<error::Result<Bounded<Answer>>> + UnwindSafe,
+) -> Run {
+    let start = Instant::now();
+    let (answer, stats) = stats::collect(|| panic::catch_unwind(f));
+    let answer = answer
+        .map_err(panic_message)
+        .and_then(|answer| answer.ok_or_else(|| format!("no part {part}")))
+        .and_then(|answer| answer.map_err(|e| e.to_string()));
+    Run {
+        day: day.number,
+        part,
+        complete: !matches!(&answer, Ok(a) if !a.complete),
+        answer: answer.map(|a| a.value),
+        elapsed: start.elapsed(),
+        stats,
+    }
+}
+
+/// `run` as a JSON object, `input` being where its input came from. The answer is
+/// always a string, as numbers may not fit a double; `type` tells what it was.
+pub fn to_json(run: &Run, input: &str) -> Json {
+    let mut fields = vec![
+        ("day", run.day.into()),
+        ("part", run.part.into()),
+        ("input", input.into()),
+    ];
+    match &run.answer {
+        Ok(answer) => {
+            let status = if run.complete { "ok" } else { "incomplete" };
+            let (answer, kind) = match answer {
+                Answer::Number(n) => (n.to_string().into(), "number"),
+                Answer::Text(s) => (s.as_str().into(), "text"),
+                Answer::None => (Json::Null, "none"),
+            };
+            fields.extend([
+                ("status", status.into()),
+                ("answer", answer),
+                ("type", kind.into()),
+            ]);
+        }
+        Err(e) => fields.extend([("status", "error".into()), ("error", e.as_str().into())]),
+    }
+    fields.push(("duration_ns", (run.elapsed.as_nanos() as u64).into()));
+    if !run.stats.is_empty() {
+        fields.push(("stats", stats::to_json(&run.stats)));
+    }
+    Json::object(fields)
+}
+
+/// Outcome of checking one manifest entry.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Mismatch {
+        expected: String,
+        found: String,
+    },
+    /// The part errored or panicked, or its input couldn't be read.
+    Fail(String),
+    Timeout,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Mismatch { .. } => write!(f, "mismatch"),
+            Status::Fail(_) => write!(f, "fail"),
+            Status::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+pub struct Verdict {
+    pub entry: Entry,
+    pub status: Status,
+    pub elapsed: Duration,
+}
+
+/// Runs the solution for `entry` and compares it with the expected answer.
+/// A part that runs past `timeout` is cancelled if its day takes a budget, and otherwise
+/// left running on its own thread. Either way its answer is dropped.
+pub fn verify(manifest: &Manifest, entry: &Entry, timeout: Duration) -> Verdict {
+    let verdict = |status, elapsed| Verdict {
+        entry: entry.clone(),
+        status,
+        elapsed,
+    };
+    let (input, expected) = match (manifest.read_input(entry), manifest.expected(entry)) {
+        (Ok(input), Ok(expected)) => (input, expected),
+        (Err(e), _) | (_, Err(e)) => return verdict(Status::Fail(e), Duration::ZERO),
+    };
+    let Some(d) = day(entry.day) else {
+        return verdict(
+            Status::Fail(format!("no day {}", entry.day)),
+            Duration::ZERO,
+        );
+    };
+    let part = entry.part;
+    let (tx, rx) = mpsc::channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let budget = Budget::unlimited().with_cancel(cancel.clone());
+    thread::spawn(move || tx.send(run_part_within(d, part, &input, &budget)));
+    let received = rx.recv_timeout(timeout);
+    cancel.store(true, Relaxed);
+    match received {
+        Ok(run) => {
+            let status = match run.answer {
+                Ok(answer) if answer.to_string() == expected => Status::Pass,
+                Ok(answer) => Status::Mismatch {
+                    expected,
+                    found: answer.to_string(),
+                },
+                Err(e) => Status::Fail(e),
+            };
+            verdict(status, run.elapsed)
+        }
+        Err(_) => verdict(Status::Timeout, timeout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runner::*;
+    #[test]
+    fn verify_statuses() {
+        let manifest = Manifest::parse(
+            "input",
+            "1 2 example01 45000\n1 2 example01 1\n1 1 missing 1\n6 1 example01 7",
+        )
+        .unwrap();
+        let statuses: Vec<_> = manifest
+            .entries
+            .iter()
+            .map(|e| {
+                verify(&manifest, e, Duration::from_secs(10))
+                    .status
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(statuses, ["pass", "mismatch", "fail", "fail"]);
+        let slow = Manifest::parse("input", "16 1 day16 1641").unwrap();
+        let verdict = verify(&slow, &slow.entries[0], Duration::from_millis(1));
+        assert_eq!(verdict.status, Status::Timeout);
+    }
+    #[test]
+    fn run_as_json() {
+        let run = run_part(day(1).unwrap(), 2, "1\n\n2\n\n3\n");
+        let json = to_json(&run, "-");
+        let ns = json.get("duration_ns").unwrap();
+        let expected = format!(
+            r#"{{"day":1,"part":2,"input":"-","status":"ok","answer":"6","type":"number","duration_ns":{ns}}}"#
+        );
+        assert_eq!(json.to_string(), expected);
+        let run = run_part(day(1).unwrap(), 2, "1\n");
+        let json = to_json(&run, "-");
+        assert_eq!(json.get("status").and_then(Json::as_str), Some("error"));
+        assert_eq!(json.get("answer"), None);
+        let input = std::fs::read_to_string("input/example24").unwrap();
+        let run = run_part(day(24).unwrap(), 1, &input);
+        let stats = to_json(&run, "input/example24").get("stats").cloned();
+        assert_eq!(
+            stats.unwrap().get("route").unwrap().get("max_depth"),
+            Some(&18.into())
+        );
+    }
+    #[test]
+    fn run_collects_stats() {
+        let input = std::fs::read_to_string("input/example24").unwrap();
+        let run = run_part(day(24).unwrap(), 2, &input);
+        let [("route", stats)] = run.stats[..] else {
+            panic!("{:?}", run.stats);
+        };
+        // The legs take 18, 23 and 13 minutes.
+        assert_eq!(stats.max_depth, 23);
+        assert!(run_part(day(1).unwrap(), 1, "1\n").stats.is_empty());
+    }
+}
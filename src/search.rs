@@ -0,0 +1,209 @@
+use crate::{budget::Budget, stats::SearchStats};
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// Distances and predecessors found by a search, plus the goal it stopped at if any.
+pub struct Search<N, C> {
+    pub dist: HashMap<N, C>,
+    pub pred: HashMap<N, N>,
+    pub goal: Option<N>,
+    /// Depth counts edges from a start.
+    pub stats: SearchStats,
+}
+
+impl<N: Hash + Eq + Clone, C: Copy> Search<N, C> {
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.dist.get(node).copied()
+    }
+    /// Distance to the goal the search stopped at.
+    pub fn goal_distance(&self) -> Option<C> {
+        self.distance(self.goal.as_ref()?)
+    }
+    /// Nodes from a start to `to`, both included.
+    pub fn path(&self, to: &N) -> Option<Vec<N>> {
+        self.dist.get(to)?;
+        let mut path = vec![to.clone()];
+        while let Some(prev) = self.pred.get(path.last().unwrap()) {
+            path.push(prev.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth first search from `starts`, stopping at the first node for which `is_goal`
+/// holds. With a goal that never holds, it's a flood fill of everything reachable.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    bfs_within(starts, neighbors, is_goal, &Budget::unlimited())
+}
+
+/// Like `bfs`, but stops without a goal when `budget` runs out. Every node
+/// expanded is spent from it.
+pub fn bfs_within<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+    budget: &Budget,
+) -> Search<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut search = Search {
+        dist: HashMap::new(),
+        pred: HashMap::new(),
+        goal: None,
+        stats: SearchStats::default(),
+    };
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if let Entry::Vacant(e) = search.dist.entry(start.clone()) {
+            e.insert(0);
+            queue.push_back((start, 0));
+        }
+    }
+    while let Some((node, d)) = queue.pop_front() {
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+        if !budget.spend() {
+            break;
+        }
+        search.stats.expand(d);
+        for next in neighbors(&node) {
+            if let Entry::Vacant(e) = search.dist.entry(next.clone()) {
+                e.insert(d + 1);
+                search.pred.insert(next.clone(), node.clone());
+                queue.push_back((next, d + 1));
+            } else {
+                search.stats.memo_hit();
+            }
+        }
+    }
+    search
+}
+
+/// Shortest paths with non-negative edge costs, stopping at the first goal reached.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, neighbors, |_| C::default(), is_goal)
+}
+
+/// Dijkstra guided by `heuristic`, which must never overestimate the cost to a goal.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut search = Search {
+        dist: HashMap::new(),
+        pred: HashMap::new(),
+        goal: None,
+        stats: SearchStats::default(),
+    };
+    // Nodes and their depths live in `nodes`, the heap holds (estimate, cost so far, index).
+    let mut nodes = Vec::new();
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        if search.dist.insert(start.clone(), C::default()).is_none() {
+            heap.push(Reverse((heuristic(&start), C::default(), nodes.len())));
+            nodes.push((start, 0));
+        }
+    }
+    while let Some(Reverse((_, cost, i))) = heap.pop() {
+        let (node, depth) = nodes[i].clone();
+        if search.dist[&node] < cost {
+            search.stats.memo_hit();
+            continue;
+        }
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+        search.stats.expand(depth);
+        for (next, step) in neighbors(&node) {
+            let next_cost = cost + step;
+            if search.dist.get(&next).is_some_and(|&d| d <= next_cost) {
+                search.stats.memo_hit();
+                continue;
+            }
+            search.dist.insert(next.clone(), next_cost);
+            search.pred.insert(next.clone(), node.clone());
+            heap.push(Reverse((
+                next_cost + heuristic(&next),
+                next_cost,
+                nodes.len(),
+            )));
+            nodes.push((next, depth + 1));
+        }
+    }
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::*;
+    // 0 -> 1 -> 3 costs 1 + 5, 0 -> 2 -> 3 costs 2 + 1.
+    fn edges(n: &u32) -> Vec<(u32, u32)> {
+        match n {
+            0 => vec![(1, 1), (2, 2)],
+            1 => vec![(3, 5)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        }
+    }
+    #[test]
+    fn bfs_path() {
+        let search = bfs([0], |n| edges(n).into_iter().map(|e| e.0), |&n| n == 3);
+        assert_eq!(search.goal_distance(), Some(2));
+        assert_eq!(search.path(&3), Some(vec![0, 1, 3]));
+        assert_eq!((search.stats.expanded, search.stats.max_depth), (3, 1));
+        let fill = bfs([0], |n| edges(n).into_iter().map(|e| e.0), |_| false);
+        assert_eq!((fill.goal, fill.dist.len()), (None, 4));
+        let budget = Budget::unlimited().with_node_limit(1);
+        let cut = bfs_within(
+            [0],
+            |n| edges(n).into_iter().map(|e| e.0),
+            |&n| n == 3,
+            &budget,
+        );
+        assert_eq!((cut.goal, cut.stats.expanded), (None, 1));
+        assert!(budget.is_exhausted());
+        assert_eq!(fill.path(&7), None);
+    }
+    #[test]
+    fn weighted_path() {
+        let search = dijkstra([0], edges, |&n| n == 3);
+        assert_eq!(search.goal_distance(), Some(3));
+        assert_eq!(search.path(&3), Some(vec![0, 2, 3]));
+        let search = astar([0], edges, |&n| 3 - n.min(3), |&n| n == 3);
+        assert_eq!(search.path(&3), Some(vec![0, 2, 3]));
+    }
+}
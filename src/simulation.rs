@@ -0,0 +1,24 @@
+/// A puzzle that plays out one step at a time, so it can be driven, paused and
+/// inspected from outside instead of only run to the end by a part function.
+pub trait Simulation {
+    /// What `snapshot` returns.
+    type State;
+    /// Advances by one step.
+    fn step(&mut self);
+    /// Whether there's nothing left to simulate. Endless simulations never are.
+    fn is_done(&self) -> bool;
+    /// The current state, for inspecting between steps.
+    fn snapshot(&self) -> Self::State;
+    /// Steps taken so far.
+    fn ticks(&self) -> usize;
+    /// Steps until done, or until `max_steps` more steps have been taken.
+    /// Returns how many steps were taken.
+    fn run(&mut self, max_steps: usize) -> usize {
+        let mut steps = 0;
+        while steps < max_steps && !self.is_done() {
+            self.step();
+            steps += 1;
+        }
+        steps
+    }
+}
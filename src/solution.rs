@@ -0,0 +1,57 @@
+use crate::error::Result;
+use std::{
+    fmt,
+    io::{self, BufRead},
+};
+
+/// Answer to one part of a puzzle, as printed by the runner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+    /// For parts that have no puzzle, i.e. day 25 part 2.
+    None,
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::Number(n as i128)
+            }
+        })*
+    };
+}
+answer_from_int!(i32, i64, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{n}"),
+            Answer::Text(s) => write!(f, "{s}"),
+            Answer::None => write!(f, "-"),
+        }
+    }
+}
+
+/// Common interface of all days, so they can be registered and run by number.
+pub trait Solution {
+    type Answer1: Into<Answer>;
+    type Answer2: Into<Answer>;
+    fn part_1(input: &str) -> Result<Self::Answer1>;
+    fn part_2(input: &str) -> Result<Self::Answer2>;
+    /// Like `part_1`, but reading the input from `input`. Days that can work through
+    /// their input line by line override this; by default it's all read in first.
+    fn part_1_read(input: &mut dyn BufRead) -> Result<Self::Answer1> {
+        Self::part_1(&io::read_to_string(input)?)
+    }
+    fn part_2_read(input: &mut dyn BufRead) -> Result<Self::Answer2> {
+        Self::part_2(&io::read_to_string(input)?)
+    }
+}
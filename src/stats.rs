@@ -0,0 +1,114 @@
+use crate::json::Json;
+use std::{cell::RefCell, fmt, ops::AddAssign};
+
+/// Counters a search keeps about its own work, for tuning its pruning.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Nodes whose children were looked at.
+    pub expanded: u64,
+    /// Nodes skipped because they, or something at least as good, were seen before.
+    pub memo_hits: u64,
+    /// Branches cut by a bound or heuristic.
+    pub pruned: u64,
+    pub max_depth: usize,
+}
+
+impl SearchStats {
+    pub fn expand(&mut self, depth: usize) {
+        self.expanded += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+    pub fn memo_hit(&mut self) {
+        self.memo_hits += 1;
+    }
+    pub fn prune(&mut self) {
+        self.pruned += 1;
+    }
+}
+
+impl AddAssign for SearchStats {
+    fn add_assign(&mut self, other: Self) {
+        self.expanded += other.expanded;
+        self.memo_hits += other.memo_hits;
+        self.pruned += other.pruned;
+        self.max_depth = self.max_depth.max(other.max_depth);
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} expanded, {} memo hits, {} pruned, max depth {}",
+            self.expanded, self.memo_hits, self.pruned, self.max_depth
+        )
+    }
+}
+
+/// Stats reported under each name, in the order the names were first reported.
+pub type Report = Vec<(&'static str, SearchStats)>;
+
+thread_local! {
+    static COLLECTED: RefCell<Option<Report>> = const { RefCell::new(None) };
+}
+
+/// Adds `stats` to what's reported under `name`, if `collect` is running on this
+/// thread. Otherwise does nothing, so searches can always report.
+pub fn report(name: &'static str, stats: SearchStats) {
+    COLLECTED.with_borrow_mut(|collected| {
+        let Some(report) = collected else {
+            return;
+        };
+        match report.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, total)) => *total += stats,
+            None => report.push((name, stats)),
+        }
+    });
+}
+
+/// An object with the stats under each name.
+pub fn to_json(report: &Report) -> Json {
+    Json::object(report.iter().map(|(name, s)| {
+        let stats = Json::object([
+            ("expanded", s.expanded.into()),
+            ("memo_hits", s.memo_hits.into()),
+            ("pruned", s.pruned.into()),
+            ("max_depth", s.max_depth.into()),
+        ]);
+        (*name, stats)
+    }))
+}
+
+/// Runs `f`, returning what it reported along with its result.
+pub fn collect<T>(f: impl FnOnce() -> T) -> (T, Report) {
+    let outer = COLLECTED.replace(Some(Vec::new()));
+    let result = f();
+    let report = COLLECTED.replace(outer).unwrap_or_default();
+    (result, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::*;
+    #[test]
+    fn collect_reports() {
+        let mut stats = SearchStats::default();
+        stats.expand(3);
+        stats.memo_hit();
+        report("ignored", stats);
+        let ((), collected) = collect(|| {
+            report("a", stats);
+            let ((), inner) = collect(|| report("b", stats));
+            assert_eq!(inner.len(), 1);
+            stats.expand(5);
+            report("a", stats);
+        });
+        let expected = SearchStats {
+            expanded: 3,
+            memo_hits: 2,
+            pruned: 0,
+            max_depth: 5,
+        };
+        assert_eq!(collected, [("a", expected)]);
+    }
+}
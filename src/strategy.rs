@@ -0,0 +1,101 @@
+use crate::{error::Result, runner, solution::Answer, Day};
+
+/// One of several implementations of a part, e.g. a naive one kept around as a
+/// reference for a faster one.
+pub struct Strategy {
+    pub name: &'static str,
+    pub part: u8,
+    pub run: fn(&str) -> Result<Answer>,
+}
+
+/// What every strategy for a part answered on one input, in registration order.
+/// Errors and panics are kept as their messages.
+pub struct Comparison {
+    pub answers: Vec<(&'static str, std::result::Result<Answer, String>)>,
+}
+
+impl Comparison {
+    /// Some strategy found an answer, and not all of them found the same.
+    pub fn disagrees(&self) -> bool {
+        let mut answers = self.answers.iter().map(|(_, a)| a);
+        let first = answers.next();
+        self.answers.iter().any(|(_, a)| a.is_ok()) && answers.any(|a| Some(a) != first)
+    }
+}
+
+/// The strategies registered for one part of a day.
+pub fn for_part(day: &Day, part: u8) -> impl Iterator<Item = &Strategy> {
+    day.strategies.iter().filter(move |s| s.part == part)
+}
+
+pub fn compare(day: &Day, part: u8, input: &str) -> Comparison {
+    let answers = for_part(day, part)
+        .map(|s| (s.name, runner::run_strategy(day, s, input).answer))
+        .collect();
+    Comparison { answers }
+}
+
+/// Removes lines from `input` for as long as the strategies keep disagreeing on
+/// it, first in large chunks, then in smaller ones down to single lines.
+pub fn shrink(day: &Day, part: u8, input: &str) -> String {
+    let join = |lines: &[&str]| lines.iter().map(|l| format!("{l}\n")).collect::<String>();
+    let mut lines: Vec<&str> = input.lines().collect();
+    let mut chunk = lines.len().div_ceil(2);
+    while chunk > 0 {
+        let mut start = 0;
+        while start < lines.len() {
+            let end = (start + chunk).min(lines.len());
+            let candidate = [&lines[..start], &lines[end..]].concat();
+            if compare(day, part, &join(&candidate)).disagrees() {
+                lines = candidate;
+            } else {
+                start = end;
+            }
+        }
+        chunk /= 2;
+    }
+    join(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::strategy::*;
+    use crate::{error::Error, DAYS};
+    use std::fs;
+    #[test]
+    fn strategies_agree() {
+        for d in DAYS.iter().filter(|d| !d.strategies.is_empty()) {
+            let input = fs::read_to_string(d.input).unwrap();
+            for part in [1, 2] {
+                assert!(!compare(d, part, &input).disagrees(), "day {}", d.number);
+            }
+        }
+    }
+    #[test]
+    fn shrink_to_disagreement() {
+        // Disagree as soon as there's a line with a 7.
+        const STRATEGIES: [Strategy; 2] = [
+            Strategy {
+                name: "count",
+                part: 1,
+                run: |input| Ok(Answer::Number(input.lines().count() as i128)),
+            },
+            Strategy {
+                name: "sevens",
+                part: 1,
+                run: |input| match input.lines().any(|l| l.contains('7')) {
+                    true => Err(Error::no_answer(1, "a seven")),
+                    false => Ok(Answer::Number(input.lines().count() as i128)),
+                },
+            },
+        ];
+        let day = Day {
+            strategies: &STRATEGIES,
+            ..DAYS[0]
+        };
+        let input = "1\n2\n3\n4\n5\n6\n17\n8\n9\n";
+        assert!(compare(&day, 1, input).disagrees());
+        assert!(!compare(&day, 1, "1\n2\n").disagrees());
+        assert_eq!(shrink(&day, 1, input), "17\n");
+    }
+}